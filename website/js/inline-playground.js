@@ -0,0 +1,24 @@
+// Replaces a docs example's code block with an embedded playground
+//
+// The links are added by the example_playground_link shortcode, which follows the example's
+// code block.
+document.querySelectorAll('.inline-playground-link').forEach((link) => {
+  link.addEventListener('click', (event) => {
+    event.preventDefault();
+
+    const iframe = document.createElement('iframe');
+    iframe.className = 'inline-playground';
+    iframe.src = link.dataset.src;
+    iframe.title = 'Koto playground';
+
+    const playgroundLink = link.closest('.playground-link');
+    const codeBlock = playgroundLink.previousElementSibling;
+    if (codeBlock && codeBlock.tagName === 'PRE') {
+      codeBlock.replaceWith(iframe);
+    } else {
+      playgroundLink.before(iframe);
+    }
+
+    link.remove();
+  });
+});
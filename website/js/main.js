@@ -1,4 +1,5 @@
 import '../sass/main.scss';
 
+import './inline-playground.js'
 import './search.js'
 import './toggle-color-scheme.js'
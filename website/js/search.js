@@ -1,11 +1,28 @@
 import Fuse from 'fuse.js'
 
+// The maximum number of results that are shown for a search
+const MAX_RESULTS = 30;
+
+// The approximate maximum length of a result's snippet
+const SNIPPET_LENGTH = 160;
+// The number of words and separators to show before the first match in a long snippet
+const SNIPPET_CONTEXT_PARTS = 8;
+
+// Common words that are left out of the index, matching STOP_WORDS in build_search_index.rs
+const STOP_WORDS = new Set([
+  'a', 'an', 'and', 'are', 'as', 'be', 'by', 'for', 'in', 'is', 'it', 'of', 'on', 'or', 'that',
+  'the', 'this', 'to', 'with',
+]);
+
 const docsVersionMeta = document.querySelector('meta[name="docs-version"]');
 if (docsVersionMeta) {
   const docsVersion = docsVersionMeta.getAttribute('content');
 
   const indexUrl = `/search-index-${docsVersion}.json`;
-  let searchIndex = null;
+  const manifestUrl = '/search-indexes.json';
+  // The loaded indexes, keyed by version
+  const searchIndexes = new Map();
+  let searchAllVersions = false;
 
   // DOM elements
   const article = document.getElementById('docs-article');
@@ -26,26 +43,41 @@ if (docsVersionMeta) {
     });
   })
 
-  // Lazily load and build the index
-  async function initSearch() {
-    if (searchIndex) {
-      return searchIndex
-    };
-
-    const data = await fetch(indexUrl).then(response => response.json());
-
-    searchIndex = new Fuse(data, {
-      keys: [
-        { name: 'title', weight: 0.8 },
-        { name: 'contents', weight: 0.3 },
-        { name: 'keywords', weight: 0.7 },
-        { name: 'module', weight: 0.2 },
-      ],
-      includeScore: true,
-      useExtendedSearch: true,
+  // Search again when switching between the current version and all versions
+  const allVersionsToggles = document.querySelectorAll('#docs-search-all-versions');
+  allVersionsToggles.forEach((toggle) => {
+    toggle.addEventListener('change', async () => {
+      searchAllVersions = toggle.checked;
+      allVersionsToggles.forEach(other => other.checked = searchAllVersions);
+
+      const input = toggle.closest('form').querySelector('#docs-search-input');
+      await search(input, input.value.trim());
     });
+  });
+
+  // Lazily load the indexes that are needed for searching
+  //
+  // The indexes are returned ordered from the newest version to the oldest.
+  async function initSearch() {
+    if (!searchAllVersions) {
+      return [await loadIndex({ version: docsVersion, url: indexUrl })];
+    }
+
+    const manifest = await fetch(manifestUrl).then(response => response.json());
+    return Promise.all(manifest.versions.map(loadIndex));
+  }
+
+  function loadIndex({ version, url }) {
+    if (!searchIndexes.has(version)) {
+      searchIndexes.set(
+        version,
+        fetch(url)
+          .then(response => response.json())
+          .then(data => makeVersionIndex(version, data))
+      );
+    }
 
-    return searchIndex;
+    return searchIndexes.get(version);
   }
 
   async function search(input, query) {
@@ -54,28 +86,43 @@ if (docsVersionMeta) {
       return;
     }
 
-    const index = await initSearch();
-    const results = index.search(query);
+    const indexes = await initSearch();
+    const results = indexes.length === 1
+      ? indexes[0].search(query).slice(0, MAX_RESULTS)
+      : searchVersions(indexes, query, docsVersion);
+    const queryTerms = tokenize(query);
 
     resultsList.innerHTML = '';
 
-    if (results.length > 0) {
-      results.filter(result => result.score < 0.4).forEach(result => {
-        const resultItem = result.item;
-        const listItem = document.createElement('li');
+    results.forEach(resultItem => {
+      const listItem = document.createElement('li');
+      // Indexes from older versions don't include breadcrumbs or snippet text
+      const location = [resultItem.module, ...(resultItem.breadcrumbs ?? [])].join(' › ');
+      const snippet = resultItem.text
+        ? `<div class="uk-text-small">${makeSnippet(resultItem, queryTerms)}</div>`
+        : '';
+      // Results from other versions are labeled with their version
+      const version = resultItem.version !== docsVersion
+        ? `<span class="uk-label uk-margin-small-left">${resultItem.version}</span>`
+        : '';
+      const availability = resultItem.availability
+        ? `<div class="uk-text-meta">${resultItem.availability}</div>`
+        : '';
 
-        listItem.innerHTML = `
+      listItem.innerHTML = `
 <a href="${resultItem.url}" class="uk-link-reset">
   ${resultItem.title}
 </a>
+${version}
 <div class="uk-text-muted uk-text-small">
-  ${resultItem.module}
+  ${location}
 </div>
+${availability}
+${snippet}
 `;
 
-        resultsList.appendChild(listItem);
-      });
-    }
+      resultsList.appendChild(listItem);
+    });
 
     if (article.hidden === false) {
       articleScrollY = window.scrollY;
@@ -138,6 +185,255 @@ if (docsVersionMeta) {
   });
 }
 
+// Prepares a version's index for searching
+function makeVersionIndex(version, data) {
+  // Indexes from older versions contain the raw entries, which need to be indexed with Fuse
+  const isRawEntries = Array.isArray(data);
+  const docs = isRawEntries ? data : data.docs;
+  const search = isRawEntries ? makeFuseSearch(data) : makePrecomputedSearch(data);
+  const indexVersion = isRawEntries ? version : data.version;
+
+  return {
+    version: indexVersion,
+    // Results are tagged with the version of the docs that they were found in
+    search: query => search(query).map(doc => ({ ...doc, version: indexVersion })),
+    // The entries in the index, used to find the versions that contain a search result
+    keys: new Set(docs.map(entryKey)),
+  };
+}
+
+// Searches the indexes of all versions
+//
+// Entries that appear in multiple versions are merged into a single result, using the entry from
+// the preferred version if it contains the entry, otherwise from the newest version.
+// The merged results are ranked by the entry's best score in any of the versions.
+function searchVersions(indexes, query, preferredVersion) {
+  const results = new Map();
+
+  for (const index of indexes) {
+    for (const doc of index.search(query)) {
+      const key = entryKey(doc);
+      const existing = results.get(key);
+
+      if (!existing) {
+        results.set(key, { ...doc, availability: describeAvailability(indexes, key) });
+      } else if (doc.version === preferredVersion) {
+        results.set(key, {
+          ...doc,
+          score: Math.max(doc.score, existing.score),
+          availability: existing.availability,
+        });
+      } else {
+        existing.score = Math.max(doc.score, existing.score);
+      }
+    }
+  }
+
+  return [...results.values()]
+    .sort((a, b) => b.score - a.score)
+    .slice(0, MAX_RESULTS);
+}
+
+// Describes when an entry was added to or removed from the docs
+function describeAvailability(indexes, key) {
+  const versions = indexes
+    .filter(index => index.keys.has(key))
+    .map(index => index.version);
+  const newest = versions[0];
+  const oldest = versions[versions.length - 1];
+
+  if (newest !== indexes[0].version) {
+    return `Removed after ${newest}`;
+  } else if (oldest === 'next') {
+    return 'Not yet released';
+  } else if (oldest !== indexes[indexes.length - 1].version) {
+    return `Available since ${oldest}`;
+  }
+
+  // The entry is in all of the indexed versions
+  return null;
+}
+
+// Identifies an entry across versions by its URL without the version, e.g. `/docs/core/list/#sort`
+function entryKey(doc) {
+  return doc.url.replace(/^\/docs\/[^/]+\//, '/docs/');
+}
+
+// Searches an index that was precomputed by `cargo xtask search-index`
+//
+// The results are sorted by their scores, which are scaled so that the best match has a score
+// of 1, making them comparable with the results from older versions' indexes.
+function makePrecomputedSearch(index) {
+  const terms = Object.entries(index.terms);
+
+  return query => {
+    const queryTerms = tokenize(query);
+    if (queryTerms.length === 0) {
+      return [];
+    }
+
+    let scores = null;
+
+    for (const { word, term } of queryTerms) {
+      const termScores = new Map();
+
+      for (const [indexTerm, postings] of terms) {
+        // Terms that start with the query's words also match so that results appear while
+        // typing, but they're ranked below exact matches.
+        const exact = indexTerm === term || indexTerm === word;
+        if (!exact && !matchesQueryTerm(indexTerm, { word, term })) {
+          continue;
+        }
+
+        // The postings alternate between doc indices and scores
+        for (let i = 0; i < postings.length; i += 2) {
+          const doc = postings[i];
+          const score = exact ? postings[i + 1] : postings[i + 1] / 2;
+          termScores.set(doc, Math.max(termScores.get(doc) ?? 0, score));
+        }
+      }
+
+      // Results need to match all of the query's terms
+      if (scores === null) {
+        scores = termScores;
+      } else {
+        for (const [doc, score] of scores) {
+          if (termScores.has(doc)) {
+            scores.set(doc, score + termScores.get(doc));
+          } else {
+            scores.delete(doc);
+          }
+        }
+      }
+    }
+
+    const results = [...scores].sort((a, b) => b[1] - a[1]);
+    const bestScore = results.length > 0 ? results[0][1] : 1;
+    return results.map(([doc, score]) => ({ ...index.docs[doc], score: score / bestScore }));
+  };
+}
+
+// Returns true if an indexed term matches one of the query's words
+function matchesQueryTerm(indexTerm, { word, term }) {
+  return indexTerm.startsWith(term) || indexTerm.startsWith(word);
+}
+
+// Returns HTML for the sentence in the doc's text that best matches the query, with the
+// matching words highlighted
+function makeSnippet(doc, queryTerms) {
+  let bestParts = null;
+  let bestMatchCount = 0;
+
+  doc.sentences.forEach((start, i) => {
+    const sentence = doc.text.slice(start, doc.sentences[i + 1] ?? doc.text.length).trim();
+    // Splitting with a capture group alternates between words and the separators between them
+    const parts = sentence.split(/([^\p{L}\p{N}]+)/u).map((text, partIndex) => ({
+      text,
+      highlight: partIndex % 2 === 0 && text.length > 0 && queryTerms.some(queryTerm =>
+        matchesQueryTerm(stem(text.toLowerCase()), queryTerm)
+      ),
+    }));
+    const matchCount = parts.filter(part => part.highlight).length;
+
+    if (bestParts === null || matchCount > bestMatchCount) {
+      bestParts = parts;
+      bestMatchCount = matchCount;
+    }
+  });
+
+  if (bestParts === null) {
+    return '';
+  }
+
+  // Long sentences are shortened to show the words around the first match
+  const sentenceLength = bestParts.reduce((length, part) => length + part.text.length, 0);
+  const firstMatch = bestParts.findIndex(part => part.highlight);
+  const first = sentenceLength > SNIPPET_LENGTH
+    ? Math.max(0, firstMatch - SNIPPET_CONTEXT_PARTS)
+    : 0;
+  let result = first > 0 ? '…' : '';
+  let length = 0;
+
+  for (const part of bestParts.slice(first)) {
+    if (length > SNIPPET_LENGTH) {
+      result += '…';
+      break;
+    }
+
+    const text = escapeHtml(part.text);
+    result += part.highlight ? `<mark>${text}</mark>` : text;
+    length += part.text.length;
+  }
+
+  return result;
+}
+
+// Builds a Fuse index from the raw entries that were used by older versions of the docs
+//
+// Fuse's scores go from 0 for a perfect match up to 1, so they're inverted to match the
+// precomputed index, where higher scores are better.
+function makeFuseSearch(entries) {
+  const fuse = new Fuse(entries, {
+    keys: [
+      { name: 'title', weight: 0.8 },
+      { name: 'contents', weight: 0.3 },
+      { name: 'keywords', weight: 0.7 },
+      { name: 'module', weight: 0.2 },
+    ],
+    includeScore: true,
+    useExtendedSearch: true,
+  });
+
+  return query => fuse
+    .search(query)
+    .filter(result => result.score < 0.4)
+    .map(result => ({ ...result.item, score: 1 - result.score }));
+}
+
+// Splits a query into words, along with their stemmed terms
+//
+// This needs to match the tokenization in build_search_index.rs.
+function tokenize(text) {
+  return text
+    .toLowerCase()
+    .split(/[^\p{L}\p{N}]+/u)
+    .filter(word => word.length > 0 && !STOP_WORDS.has(word))
+    .map(word => ({ word, term: stem(word) }));
+}
+
+// A minimal suffix-stripping stemmer, matching `stem` in build_search_index.rs
+function stem(word) {
+  let result = word;
+
+  if (word.endsWith('sses')) {
+    result = word.slice(0, -2);
+  } else if (word.endsWith('ies') && word.length > 4) {
+    result = word.slice(0, -3) + 'y';
+  } else if (
+    word.length > 3
+    && word.endsWith('s')
+    && !['ss', 'us', 'is'].some(suffix => word.endsWith(suffix))
+  ) {
+    result = word.slice(0, -1);
+  }
+
+  for (const suffix of ['ing', 'ed']) {
+    if (result.endsWith(suffix) && result.length - suffix.length >= 4) {
+      result = result.slice(0, -suffix.length);
+      break;
+    }
+  }
+
+  return result;
+}
+
+function escapeHtml(text) {
+  return text
+    .replaceAll('&', '&amp;')
+    .replaceAll('<', '&lt;')
+    .replaceAll('>', '&gt;');
+}
+
 function scrollIntoViewIfNeeded(element) {
   const parent = element.parentNode;
 
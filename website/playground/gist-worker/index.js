@@ -6,13 +6,24 @@ import { Router } from 'itty-router'
 const router = Router()
 const octokit = new Octokit({ auth: GITHUB_TOKEN })
 
-router.get('/play/create-gist/version', () => new Response('0.1.3'))
+router.get('/play/create-gist/version', () => new Response('0.2.0'))
 
 router.post('/play/create-gist', async (request) => {
-  const script = await request.text()
+  let files
+  if (request.headers.get('content-type')?.includes('application/json')) {
+    // Multi-file projects are sent as a map of file names to contents
+    const body = await request.json()
+    files = Object.fromEntries(
+      Object.entries(body.files).map(([name, content]) => [name, { content }]),
+    )
+  } else {
+    // Earlier versions of the playground send a single script as text
+    const script = await request.text()
+    files = { 'playground.koto': { content: script } }
+  }
 
   const { data: gist } = await octokit.rest.gists.create({
-    files: { 'playground.koto': { content: script } },
+    files,
     public: true,
   })
 
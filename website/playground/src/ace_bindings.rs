@@ -1,10 +1,14 @@
-use {js_sys::Function, wasm_bindgen::prelude::*};
+use {
+    js_sys::{Array, Function},
+    wasm_bindgen::prelude::*,
+};
 
 #[wasm_bindgen]
 extern "C" {
     pub type Ace;
     pub type AceEditor;
     pub type AceSession;
+    pub type AceRange;
 
     #[wasm_bindgen(method)]
     pub fn edit(this: &Ace, id: &str) -> AceEditor;
@@ -41,9 +45,48 @@ extern "C" {
 
     #[wasm_bindgen(method)]
     pub fn on(this: &AceSession, event_name: &str, callback: &Function);
+
+    #[wasm_bindgen(method, js_name = setAnnotations)]
+    pub fn set_annotations(this: &AceSession, annotations: &Array);
+
+    #[wasm_bindgen(method, js_name = clearAnnotations)]
+    pub fn clear_annotations(this: &AceSession);
+
+    #[wasm_bindgen(method, js_name = addMarker)]
+    pub fn add_marker(
+        this: &AceSession,
+        range: &AceRange,
+        class: &str,
+        marker_type: &str,
+        in_front: bool,
+    ) -> u32;
+
+    #[wasm_bindgen(method, js_name = removeMarker)]
+    pub fn remove_marker(this: &AceSession, marker_id: u32);
 }
 
 #[wasm_bindgen(inline_js = "export function get_ace() { return ace; }")]
 extern "C" {
     pub fn get_ace() -> Ace;
 }
+
+#[wasm_bindgen(inline_js = r#"
+export function create_range(start_row, start_column, end_row, end_column) {
+  const Range = ace.require("ace/range").Range;
+  return new Range(start_row, start_column, end_row, end_column);
+}
+
+export function create_annotation(row, column, text, type) {
+  return { row, column, text, type };
+}
+"#)]
+extern "C" {
+    pub fn create_range(
+        start_row: u32,
+        start_column: u32,
+        end_row: u32,
+        end_column: u32,
+    ) -> AceRange;
+
+    pub fn create_annotation(row: u32, column: u32, text: &str, annotation_type: &str) -> JsValue;
+}
@@ -23,12 +23,10 @@ pub fn editor(props: &Props) -> Html {
                 let ace = get_ace();
                 let editor = ace.edit(EDITOR_ID);
                 editor.set_show_print_margin(false);
-                editor.set_font_size(14);
 
                 let session = editor.get_session();
                 session.set_mode("ace/mode/koto");
                 session.set_use_soft_tabs(true);
-                session.set_tab_size(2);
 
                 let editor_changed_callback =
                     Closure::wrap({ Box::new(move || on_changed.emit(())) } as Box<dyn FnMut()>);
@@ -6,9 +6,15 @@ use {
 #[derive(PartialEq, Properties)]
 pub struct Props {
     pub auto_run: bool,
+    // True while a script is running or a REPL expression is being evaluated
+    pub running: bool,
+    pub stdin_visible: bool,
     pub on_run_clicked: Callback<()>,
+    pub on_stop_clicked: Callback<()>,
     pub on_auto_run_clicked: Callback<()>,
+    pub on_stdin_clicked: Callback<()>,
     pub on_share_clicked: Callback<()>,
+    pub on_settings_clicked: Callback<()>,
     pub on_script_selected: Callback<&'static str>,
 }
 
@@ -23,6 +29,14 @@ pub fn editor_toolbar(props: &Props) -> Html {
                 on_clicked={props.on_run_clicked.clone()}
             />
 
+            <ToolbarButton
+                icon_left="ban"
+                caption="Stop"
+                tooltip="Stop the running script"
+                disabled={!props.running}
+                on_clicked={props.on_stop_clicked.clone()}
+            />
+
             <ToolbarButton
                 icon_left="refresh"
                 caption="Watch"
@@ -31,6 +45,14 @@ pub fn editor_toolbar(props: &Props) -> Html {
                 on_clicked={props.on_auto_run_clicked.clone()}
             />
 
+            <ToolbarButton
+                icon_left="pencil"
+                caption="Input"
+                tooltip="Show the input that's provided to the script via stdin"
+                toggled={props.stdin_visible}
+                on_clicked={props.on_stdin_clicked.clone()}
+            />
+
             <div class="horizontal-spacer"></div>
 
             <ToolbarButton
@@ -40,6 +62,13 @@ pub fn editor_toolbar(props: &Props) -> Html {
                 on_clicked={props.on_share_clicked.clone()}
             />
 
+            <ToolbarButton
+                icon_left="cog"
+                caption="Settings"
+                tooltip="Change the playground's settings"
+                on_clicked={props.on_settings_clicked.clone()}
+            />
+
             <ExamplesDialog on_script_selected={props.on_script_selected.clone()} />
         </div>
     }
@@ -0,0 +1,61 @@
+use {cloned::cloned, yew::prelude::*};
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub file_names: Vec<String>,
+    pub active_file: usize,
+    pub on_file_selected: Callback<usize>,
+    pub on_add_clicked: Callback<()>,
+    pub on_remove_clicked: Callback<usize>,
+}
+
+#[function_component(FileTabs)]
+pub fn file_tabs(props: &Props) -> Html {
+    let tabs = props.file_names.iter().enumerate().map(|(index, name)| {
+        let onclick = props.on_file_selected.reform(move |_: MouseEvent| index);
+
+        // The main script can't be removed
+        let remove_button = if index > 0 {
+            let onclick = Callback::from({
+                cloned!(props.on_remove_clicked);
+                move |event: MouseEvent| {
+                    event.stop_propagation();
+                    on_remove_clicked.emit(index)
+                }
+            });
+            html! {
+                <span
+                    class="uk-margin-small-left"
+                    uk-icon="icon: close; ratio: 0.7"
+                    uk-tooltip="title: Remove the module; delay: 1000"
+                    {onclick}
+                ></span>
+            }
+        } else {
+            html! {}
+        };
+
+        html! {
+            <li class={classes!((index == props.active_file).then_some("uk-active"))}>
+                <a {onclick}>
+                    {format!("{name}.koto")}
+                    {remove_button}
+                </a>
+            </li>
+        }
+    });
+
+    html! {
+        <ul class="uk-tab file-tabs">
+            { for tabs }
+            <li>
+                <a
+                    uk-tooltip="title: Add a module; delay: 1000"
+                    onclick={props.on_add_clicked.reform(|_: MouseEvent| ())}
+                >
+                    <span uk-icon="icon: plus; ratio: 0.7"></span>
+                </a>
+            </li>
+        </ul>
+    }
+}
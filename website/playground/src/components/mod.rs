@@ -1,6 +1,11 @@
 pub mod editor;
 pub mod editor_toolbar;
 pub mod examples;
+pub mod file_tabs;
+pub mod output_toolbar;
 pub mod playground;
+pub mod repl;
+pub mod settings;
 pub mod share;
+pub mod status_bar;
 pub mod toolbar_button;
@@ -0,0 +1,50 @@
+use {super::toolbar_button::ToolbarButton, yew::prelude::*};
+
+// Which kinds of output should be shown in the output pane
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OutputFilter {
+    All,
+    Stdout,
+    Stderr,
+}
+
+impl OutputFilter {
+    // The class that gets applied to the output pane to hide filtered output
+    pub fn css_class(&self) -> Option<&'static str> {
+        match self {
+            Self::All => None,
+            Self::Stdout => Some("hide-stderr"),
+            Self::Stderr => Some("hide-stdout"),
+        }
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub filter: OutputFilter,
+    pub on_filter_changed: Callback<OutputFilter>,
+}
+
+#[function_component(OutputToolbar)]
+pub fn output_toolbar(props: &Props) -> Html {
+    let filter_button = |filter: OutputFilter, caption: &'static str, tooltip: &'static str| {
+        html! {
+            <ToolbarButton
+                {caption}
+                {tooltip}
+                toggled={props.filter == filter}
+                on_clicked={props.on_filter_changed.reform(move |_: ()| filter)}
+            />
+        }
+    };
+
+    html! {
+        <div class="editor-toolbar">
+            <div class="horizontal-spacer"></div>
+
+            { filter_button(OutputFilter::All, "All", "Show all output") }
+            { filter_button(OutputFilter::Stdout, "Stdout", "Only show stdout output") }
+            { filter_button(OutputFilter::Stderr, "Stderr", "Only show errors and stderr output") }
+        </div>
+    }
+}
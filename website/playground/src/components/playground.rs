@@ -1,20 +1,46 @@
 use {
-    super::{editor::Editor, editor_toolbar::EditorToolbar, share::Share},
+    super::{
+        editor::Editor,
+        editor_toolbar::EditorToolbar,
+        file_tabs::FileTabs,
+        output_toolbar::{OutputFilter, OutputToolbar},
+        repl::{Repl, ReplEntry},
+        settings::SettingsDialog,
+        share::Share,
+        status_bar::StatusBar,
+        toolbar_button::ToolbarButton,
+    },
     crate::{
-        ace_bindings::AceEditor, koto_wrapper::KotoWrapper, show_notification,
+        ace_bindings::{create_annotation, create_range, AceEditor},
+        koto_worker::KotoWorker,
+        script_files::ScriptFiles,
+        settings::{KeyBindings, Settings},
+        show_notification,
         stored_value::StoredValue,
+        worker_protocol::{ExecutionStats, KotoMessage, ScriptError},
     },
     // gloo_console::log,
     gloo_events::EventListener,
     gloo_net::http::Request,
+    gloo_timers::callback::Timeout,
     gloo_utils::{document, window},
+    js_sys::{encode_uri_component, Array},
     serde::Deserialize,
-    std::collections::HashMap,
+    std::{collections::HashMap, str::FromStr},
     wasm_bindgen::{closure::Closure, JsCast},
-    web_sys::{Element, MutationObserver, MutationObserverInit, UrlSearchParams},
+    web_sys::{
+        Element, HtmlTextAreaElement, MutationObserver, MutationObserverInit, UrlSearchParams,
+    },
     yew::prelude::*,
 };
 
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    // True when the playground is embedded in another page, e.g. in the docs
+    #[prop_or_default]
+    pub embedded: bool,
+}
+
 #[derive(PartialEq, Clone, Copy)]
 pub struct PlaygroundContext {
     pub dark_mode: bool,
@@ -23,34 +49,79 @@ pub struct PlaygroundContext {
 pub enum Msg {
     EditorInitialized { editor: AceEditor },
     EditorChanged,
-    ScriptLoaded { contents: String },
-    PostScriptLoaded,
+    ScriptFilesLoaded { files: ScriptFiles },
     ScriptMenuChanged { contents: &'static str },
+    FileSelected { index: usize },
+    AddFileClicked,
+    RemoveFileClicked { index: usize },
+    AutoRunTimeout,
     RunButtonClicked,
+    StopButtonClicked,
     AutoRunButtonClicked,
+    StdinButtonClicked,
+    StdinChanged { stdin: String },
     ShareButtonClicked,
     ShareModalClosed,
+    OpenInPlaygroundClicked,
+    SettingsButtonClicked,
+    SettingsChanged { settings: Settings },
+    SettingsModalClosed,
     BeforeUnload,
     DocumentAttributesChanged,
     ShowError { error: String },
+    Koto(KotoMessage),
+    OutputFilterChanged { filter: OutputFilter },
+    ReplSubmitted { expression: String },
+}
+
+#[derive(Clone, Copy)]
+enum OutputKind {
+    Stdout,
+    Stderr,
+    Error,
+}
+
+impl OutputKind {
+    fn classes(&self) -> &'static str {
+        match self {
+            Self::Stdout => "output-stdout",
+            Self::Stderr => "output-stderr uk-text-warning",
+            Self::Error => "output-stderr uk-text-danger",
+        }
+    }
 }
 
 pub struct Playground {
     playground_context: PlaygroundContext,
 
     script_output_ref: NodeRef,
+    output_filter: OutputFilter,
+    repl_entries: Vec<ReplEntry>,
+    // The id that's given to the next REPL entry
+    next_repl_id: u32,
 
     editor: Option<AceEditor>,
-    koto: Option<KotoWrapper>,
+    // The id of the Ace marker that highlights the location of the last error
+    error_marker: Option<u32>,
+    koto: KotoWorker,
 
     auto_run_enabled: bool,
-
-    script: StoredValue<String>,
-    vim_bindings_enabled: StoredValue<bool>,
+    // The pending auto-run, which gets replaced when further changes are made
+    auto_run_timeout: Option<Timeout>,
+    // The scripts and input that were used in the last run
+    last_run: Option<ScriptFiles>,
+    script_running: bool,
+    // Statistics for the most recent script run, if it has finished
+    execution_stats: Option<ExecutionStats>,
+    stdin_visible: bool,
+
+    script_files: StoredValue<ScriptFiles>,
+    // The index of the file that's currently shown in the editor
+    active_file: usize,
+    settings: StoredValue<Settings>,
 
     show_share_dialog: bool,
-
-    ignore_editor_changed: bool,
+    show_settings_dialog: bool,
 
     _event_listeners: Vec<EventListener>,
     _document_attributes_listener: MutationObserver,
@@ -71,49 +142,59 @@ impl Playground {
     }
 
     fn setup_editor(&mut self, ctx: &Context<Self>) {
-        let script = {
-            let url_params = UrlSearchParams::new_with_str(
-                &window()
-                    .location()
-                    .search()
-                    .expect("Missing location search string"),
-            )
-            .expect("Failed to create UrlSearchParams");
-
-            if let Some(gist) = url_params.get("gist") {
-                ctx.link().send_future(async move {
-                    match Request::get(&format!("https://api.github.com/gists/{gist}"))
-                        .send()
-                        .await
-                    {
-                        Ok(response) => match response.json::<Gist>().await {
-                            Ok(gist) => match gist.files.values().next() {
-                                Some(file) => Msg::ScriptLoaded {
-                                    contents: file.content.clone(),
-                                },
-                                None => Msg::ShowError {
-                                    error: "The gist doesn't contain any files".into(),
-                                },
-                            },
-                            Err(_) => Msg::ShowError {
-                                error: "Failed to load gist".into(),
+        let url_params = UrlSearchParams::new_with_str(
+            &window()
+                .location()
+                .search()
+                .expect("Missing location search string"),
+        )
+        .expect("Failed to create UrlSearchParams");
+
+        if let Some(gist) = url_params.get("gist") {
+            ctx.link().send_future(async move {
+                match Request::get(&format!("https://api.github.com/gists/{gist}"))
+                    .send()
+                    .await
+                {
+                    Ok(response) => match response.json::<Gist>().await {
+                        Ok(gist) => match ScriptFiles::from_named_files(
+                            gist.files
+                                .into_iter()
+                                .map(|(name, file)| (name, file.content)),
+                        ) {
+                            Some(files) => Msg::ScriptFilesLoaded { files },
+                            None => Msg::ShowError {
+                                error: "The gist doesn't contain any files".into(),
                             },
                         },
-                        Err(error) => Msg::ShowError {
-                            error: format!("Failed to access gist (error: '{error}')"),
+                        Err(_) => Msg::ShowError {
+                            error: "Failed to load gist".into(),
                         },
-                    }
-                });
-                "".into()
-            } else if let Some(script) = url_params.get("script") {
-                script
-            } else {
-                self.script.as_ref().clone()
+                    },
+                    Err(error) => Msg::ShowError {
+                        error: format!("Failed to access gist (error: '{error}')"),
+                    },
+                }
+            });
+            self.script_files.set(ScriptFiles::default());
+        } else if let Some(files) = url_params.get("files") {
+            match ScriptFiles::from_str(&files) {
+                Ok(files) => self.script_files.set(files),
+                Err(_) => show_notification("Failed to load the scripts from the URL", "error"),
             }
-        };
+        } else if let Some(script) = url_params.get("script") {
+            let mut script_files = ScriptFiles::new(&script);
+            if let Some(stdin) = url_params.get("stdin") {
+                script_files.set_stdin(stdin);
+            }
+            self.script_files.set(script_files);
+        }
 
-        self.set_editor_contents(&script);
-        self.set_vim_bindings_enabled(self.vim_bindings_enabled.get());
+        self.active_file = 0;
+        self.stdin_visible = !self.script_files.as_ref().stdin().is_empty();
+        let main_script = self.script_files.as_ref().main_script().to_string();
+        self.set_editor_contents(&main_script);
+        self.apply_editor_settings();
         self.update_editor_theme();
     }
 
@@ -126,26 +207,247 @@ impl Playground {
             });
     }
 
-    // This was available via a UI toggle, but can now be enabled via:
-    // document.documentElement.setAttribute("editor-bindings", "vim");
-    //
-    // At some point a settings dialog will be added and vim bindings can be re-enabled,
-    // alongside other bindings, e.g. emacs.
-    fn set_vim_bindings_enabled(&mut self, enabled: bool) {
-        self.vim_bindings_enabled.set(enabled);
-        self.get_editor()
-            .set_keyboard_handler(if enabled { "ace/keyboard/vim" } else { "" });
+    fn apply_editor_settings(&self) {
+        let settings = self.settings.as_ref();
+        let editor = self.get_editor();
+        editor.set_font_size(settings.font_size);
+        editor.set_keyboard_handler(settings.key_bindings.ace_handler());
+        editor.get_session().set_tab_size(settings.tab_size);
+    }
+
+    // Schedules the script to be run once the auto-run delay has passed without further changes
+    fn schedule_auto_run(&mut self, ctx: &Context<Self>) {
+        if !self.auto_run_enabled {
+            return;
+        }
+
+        // Replacing the timeout cancels the previously scheduled run
+        self.auto_run_timeout = Some(Timeout::new(self.settings.as_ref().auto_run_debounce_ms, {
+            let link = ctx.link().clone();
+            move || link.send_message(Msg::AutoRunTimeout)
+        }));
     }
 
     fn compile_and_run_script(&mut self) {
-        let koto = self.koto.as_mut().expect("Missing koto wrapper");
-        koto.compile_and_run_script(self.script.as_ref());
+        self.auto_run_timeout = None;
+        self.last_run = Some(self.script_files.as_ref().clone());
+
+        if self.script_running {
+            // The worker is busy, so restart it rather than waiting for the script to finish.
+            // Pending REPL evaluations are left to finish, the worker handles requests in order.
+            self.restart_worker();
+        }
+
+        self.clear_output();
+        self.clear_error_markers();
+        self.script_running = true;
+        self.execution_stats = None;
+        let script_files = self.script_files.as_ref();
+        self.koto.run(
+            script_files.main_script(),
+            script_files.modules(),
+            script_files.stdin(),
+            self.settings.as_ref().run_settings(),
+        );
+    }
+
+    fn stop_script(&mut self) {
+        if self.script_running {
+            self.restart_worker();
+            self.script_running = false;
+            self.append_output("\nScript stopped", OutputKind::Error);
+        } else if self.repl_evaluation_pending() {
+            self.restart_worker();
+        }
+    }
+
+    // REPL entries are waiting for their results while the worker evaluates them
+    fn repl_evaluation_pending(&self) -> bool {
+        self.repl_entries.iter().any(|entry| entry.result.is_none())
+    }
+
+    fn restart_worker(&mut self) {
+        self.koto.stop();
+
+        for entry in self.repl_entries.iter_mut() {
+            if entry.result.is_none() {
+                entry.result = Some(Err("Evaluation stopped".into()));
+            }
+        }
+    }
+
+    fn evaluate_repl_expression(&mut self, expression: String) {
+        let id = self.next_repl_id;
+        self.next_repl_id += 1;
+
+        self.koto
+            .evaluate(id, &expression, self.settings.as_ref().output_limit);
+        self.repl_entries.push(ReplEntry {
+            id,
+            input: expression,
+            output: String::new(),
+            result: None,
+        });
+    }
+
+    fn show_script_error(&mut self, error: &ScriptError, prefix: &str) {
+        self.append_output(&format!("{prefix}{}", error.message), OutputKind::Error);
+
+        let Some(span) = error.span else {
+            return;
+        };
+
+        // Only show markers when the file containing the error is in the editor
+        let error_file = match &error.module {
+            Some(module) => self
+                .script_files
+                .as_ref()
+                .files()
+                .iter()
+                .position(|file| &file.name == module),
+            None => Some(0),
+        };
+        if error_file != Some(self.active_file) {
+            return;
+        }
+
+        let session = self.get_editor().get_session();
+
+        let summary = error.message.lines().next().unwrap_or_default();
+        session.set_annotations(&Array::of1(&create_annotation(
+            span.start_line,
+            span.start_column,
+            summary,
+            "error",
+        )));
+
+        // Make sure that zero-length spans are still visible
+        let end_column = if span.start_line == span.end_line {
+            span.end_column.max(span.start_column + 1)
+        } else {
+            span.end_column
+        };
+        let range = create_range(
+            span.start_line,
+            span.start_column,
+            span.end_line,
+            end_column,
+        );
+        self.error_marker =
+            Some(session.add_marker(&range, "playground-error-marker", "text", false));
+    }
+
+    fn clear_error_markers(&mut self) {
+        let session = self.get_editor().get_session();
+        session.clear_annotations();
+        if let Some(marker) = self.error_marker.take() {
+            session.remove_marker(marker);
+        }
+    }
+
+    // The Ace editor can send multiple on_changed events when its contents are set, the events
+    // are handled after the contents have been updated so they don't cause any extra runs.
+    fn load_script_files(&mut self, ctx: &Context<Self>, files: ScriptFiles) {
+        self.stdin_visible = !files.stdin().is_empty();
+        self.script_files.set(files);
+        self.active_file = 0;
+        let main_script = self.script_files.as_ref().main_script().to_string();
+        self.set_editor_contents(&main_script);
+        self.schedule_auto_run(ctx);
+    }
+
+    fn select_file(&mut self, index: usize) {
+        self.active_file = index;
+        let contents = self.script_files.as_ref().contents(index).to_string();
+        self.set_editor_contents(&contents);
+        self.clear_error_markers();
+    }
+
+    // A compact view for the embedded playground, with the editor above the output
+    fn view_embedded(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <ContextProvider<PlaygroundContext> context={self.playground_context}>
+                <div class="playground">
+                    <div class="editor-area">
+                        <div class="editor-toolbar">
+                            <ToolbarButton
+                                icon_left="play"
+                                caption="Run"
+                                tooltip="Run the script"
+                                on_clicked={ctx.link().callback(|_| Msg::RunButtonClicked)}
+                            />
+
+                            <ToolbarButton
+                                icon_left="ban"
+                                caption="Stop"
+                                tooltip="Stop the running script"
+                                disabled={!self.script_running}
+                                on_clicked={ctx.link().callback(|_| Msg::StopButtonClicked)}
+                            />
+
+                            <div class="horizontal-spacer"></div>
+
+                            <ToolbarButton
+                                icon_left="push"
+                                caption="Open"
+                                tooltip="Open the script in the playground"
+                                on_clicked={
+                                    ctx.link().callback(|_| Msg::OpenInPlaygroundClicked)
+                                }
+                            />
+                        </div>
+
+                        <Editor
+                            on_initialized={
+                                ctx.link().callback(|editor| Msg::EditorInitialized { editor })
+                            }
+                            on_changed={ctx.link().callback(|_| Msg::EditorChanged)}
+                        />
+                    </div>
+
+                    <div class="output-area">
+                        <div
+                          ref={self.script_output_ref.clone()}
+                          class={classes!(
+                              "playground-output",
+                              "fixed-mono",
+                              "uk-textarea",
+                              "uk-form-small",
+                              "flex-grow",
+                          )}
+                        ></div>
+                    </div>
+                </div>
+            </ContextProvider<PlaygroundContext>>
+        }
+    }
+
+    fn script_output(&self) -> Element {
+        self.script_output_ref
+            .cast::<Element>()
+            .expect("Missing script output")
+    }
+
+    fn clear_output(&self) {
+        self.script_output().set_inner_html("");
+    }
+
+    fn append_output(&self, output: &str, kind: OutputKind) {
+        let output_span = document()
+            .create_element("span")
+            .expect("Failed to create output element");
+        output_span.set_class_name(kind.classes());
+        output_span.set_text_content(Some(output));
+
+        let script_output = self.script_output();
+        script_output.append_child(&output_span).unwrap();
+        script_output.set_scroll_top(script_output.scroll_height());
     }
 }
 
 impl Component for Playground {
     type Message = Msg;
-    type Properties = ();
+    type Properties = Props;
 
     fn create(ctx: &Context<Self>) -> Self {
         let color_scheme_attr = document()
@@ -176,15 +478,35 @@ impl Component for Playground {
         Self {
             playground_context,
             script_output_ref: NodeRef::default(),
+            output_filter: OutputFilter::All,
+            repl_entries: Vec::new(),
+            next_repl_id: 0,
             editor: None,
-            koto: None,
-            script: StoredValue::new_with_default("script", || {
-                include_str!("../../examples/intro/fizz_buzz.koto").into()
-            }),
-            vim_bindings_enabled: StoredValue::new("vim-bindings-enabled"),
+            error_marker: None,
+            koto: KotoWorker::new(ctx.link().callback(Msg::Koto)),
+            // Embedded playgrounds start with the script from the URL, and shouldn't show the
+            // scripts that were saved in the main playground
+            script_files: if ctx.props().embedded {
+                StoredValue::temporary(ScriptFiles::default())
+            } else {
+                StoredValue::new_with_default("script-files", || {
+                    // Fall back to the script that was saved by earlier versions of the playground
+                    let script = StoredValue::<String>::new_with_default("script", || {
+                        include_str!("../../examples/intro/fizz_buzz.koto").into()
+                    });
+                    ScriptFiles::new(script.as_ref())
+                })
+            },
+            active_file: 0,
+            settings: Settings::load(),
             auto_run_enabled: true,
+            auto_run_timeout: None,
+            last_run: None,
+            script_running: false,
+            execution_stats: None,
+            stdin_visible: false,
             show_share_dialog: false,
-            ignore_editor_changed: false,
+            show_settings_dialog: false,
             _event_listeners: vec![EventListener::new(&window(), "beforeunload", {
                 let link = ctx.link().clone();
                 move |_| link.send_message(Msg::BeforeUnload)
@@ -202,40 +524,69 @@ impl Component for Playground {
                 false
             }
             Msg::EditorChanged => {
-                if self.ignore_editor_changed {
-                    false
-                } else {
-                    let script = self.get_editor_contents();
-                    self.script.set(script);
-                    if self.auto_run_enabled {
-                        self.compile_and_run_script();
-                    }
-                    true
+                let script = self.get_editor_contents();
+                self.script_files
+                    .as_mut()
+                    .set_contents(self.active_file, script);
+                self.schedule_auto_run(ctx);
+                false
+            }
+            Msg::ScriptFilesLoaded { files } => {
+                self.load_script_files(ctx, files);
+                true
+            }
+            Msg::ScriptMenuChanged { contents } => {
+                self.load_script_files(ctx, ScriptFiles::new(contents));
+                true
+            }
+            Msg::FileSelected { index } => {
+                if index != self.active_file {
+                    self.select_file(index);
                 }
+                true
             }
-            Msg::ScriptLoaded { contents } => {
-                // We only want to compile the script once, and the Ace editor can send multiple
-                // on_changed events when setting its contents.
-                self.ignore_editor_changed = true;
-                self.set_editor_contents(&contents);
-                ctx.link().send_message(Msg::PostScriptLoaded);
+            Msg::AddFileClicked => {
+                let name = window().prompt_with_message("Module name:").ok().flatten();
+                if let Some(name) = name {
+                    match self.script_files.as_mut().add_module(&name) {
+                        Ok(index) => self.select_file(index),
+                        Err(error) => show_notification(&error, "warning"),
+                    }
+                }
                 true
             }
-            Msg::PostScriptLoaded => {
-                self.ignore_editor_changed = false;
-                ctx.link().send_message(Msg::EditorChanged);
-                false
+            Msg::RemoveFileClicked { index } => {
+                let name = &self.script_files.as_ref().files()[index].name;
+                let confirmed = window()
+                    .confirm_with_message(&format!("Remove '{name}.koto'?"))
+                    .unwrap_or(false);
+                if confirmed {
+                    self.script_files.as_mut().remove_module(index);
+                    if self.active_file == index {
+                        self.select_file(0);
+                    } else if self.active_file > index {
+                        self.active_file -= 1;
+                    }
+                    self.schedule_auto_run(ctx);
+                }
+                true
             }
-            Msg::ScriptMenuChanged { contents } => {
-                self.ignore_editor_changed = true;
-                self.set_editor_contents(contents);
-                ctx.link().send_message(Msg::PostScriptLoaded);
-                false
+            Msg::AutoRunTimeout => {
+                self.auto_run_timeout = None;
+                // Skip the run if nothing has changed since the last run, e.g. after an undo
+                if self.last_run.as_ref() != Some(self.script_files.as_ref()) {
+                    self.compile_and_run_script();
+                }
+                true
             }
             Msg::RunButtonClicked => {
                 self.compile_and_run_script();
                 true
             }
+            Msg::StopButtonClicked => {
+                self.stop_script();
+                true
+            }
             Msg::AutoRunButtonClicked => {
                 self.auto_run_enabled = !self.auto_run_enabled;
                 if self.auto_run_enabled {
@@ -243,6 +594,15 @@ impl Component for Playground {
                 }
                 true
             }
+            Msg::StdinButtonClicked => {
+                self.stdin_visible = !self.stdin_visible;
+                true
+            }
+            Msg::StdinChanged { stdin } => {
+                self.script_files.as_mut().set_stdin(stdin);
+                self.schedule_auto_run(ctx);
+                false
+            }
             Msg::ShareButtonClicked => {
                 // self.copy_link_to_clipboard();
                 self.show_share_dialog = true;
@@ -252,9 +612,37 @@ impl Component for Playground {
                 self.show_share_dialog = false;
                 true
             }
+            Msg::SettingsButtonClicked => {
+                self.show_settings_dialog = true;
+                true
+            }
+            Msg::SettingsChanged { settings } => {
+                self.settings.set(settings);
+                self.settings.save();
+                self.apply_editor_settings();
+                true
+            }
+            Msg::SettingsModalClosed => {
+                self.show_settings_dialog = false;
+                true
+            }
+            Msg::OpenInPlaygroundClicked => {
+                let location = window().location();
+                let url = format!(
+                    "{origin}{path}?script={script}",
+                    origin = location.origin().expect("Missing location origin"),
+                    path = location.pathname().expect("Missing location pathname"),
+                    script = encode_uri_component(self.script_files.as_ref().main_script())
+                );
+                window().open_with_url_and_target(&url, "_blank").ok();
+                false
+            }
             Msg::BeforeUnload => {
-                self.script.save();
-                self.vim_bindings_enabled.save();
+                // Embedded playgrounds shouldn't overwrite the main playground's saved state
+                if !ctx.props().embedded {
+                    self.script_files.save();
+                    self.settings.save();
+                }
                 false
             }
             Msg::DocumentAttributesChanged => {
@@ -272,13 +660,16 @@ impl Component for Playground {
                     self.update_editor_theme();
                 }
 
-                let vim_bindings_enabled = matches!(
-                    document_element.get_attribute("editor-bindings"),
-                    Some(scheme) if scheme == "vim");
-                let vim_bindings_enabled_changed =
-                    self.vim_bindings_enabled.get() != vim_bindings_enabled;
-                if vim_bindings_enabled_changed {
-                    self.set_vim_bindings_enabled(vim_bindings_enabled);
+                // Key bindings can also be set via the document, e.g.
+                // document.documentElement.setAttribute("editor-bindings", "vim");
+                let key_bindings = document_element
+                    .get_attribute("editor-bindings")
+                    .and_then(|bindings| KeyBindings::from_attribute(&bindings));
+                if let Some(key_bindings) = key_bindings {
+                    if self.settings.as_ref().key_bindings != key_bindings {
+                        self.settings.as_mut().key_bindings = key_bindings;
+                        self.apply_editor_settings();
+                    }
                 }
 
                 dark_mode_changed
@@ -287,26 +678,145 @@ impl Component for Playground {
                 show_notification(&error, "error");
                 false
             }
+            Msg::Koto(message) => match message {
+                KotoMessage::Print(output) => {
+                    self.append_output(&output, OutputKind::Stdout);
+                    false
+                }
+                KotoMessage::Stderr(output) => {
+                    self.append_output(&output, OutputKind::Stderr);
+                    false
+                }
+                KotoMessage::CompileError(error) => {
+                    self.show_script_error(&error, "Error while compiling script: ");
+                    false
+                }
+                KotoMessage::RuntimeError(error) => {
+                    self.show_script_error(&error, "");
+                    false
+                }
+                KotoMessage::ReplResult { id, output, result } => {
+                    if let Some(entry) = self.repl_entries.iter_mut().find(|entry| entry.id == id) {
+                        entry.output = output;
+                        entry.result = Some(result);
+                    }
+                    true
+                }
+                KotoMessage::Stats(stats) => {
+                    self.execution_stats = Some(stats);
+                    false
+                }
+                KotoMessage::Finished => {
+                    self.script_running = false;
+                    true
+                }
+                // Handled by the worker wrapper
+                KotoMessage::Ready => false,
+            },
+            Msg::OutputFilterChanged { filter } => {
+                self.output_filter = filter;
+                true
+            }
+            Msg::ReplSubmitted { expression } => {
+                self.evaluate_repl_expression(expression);
+                true
+            }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
+        if ctx.props().embedded {
+            return self.view_embedded(ctx);
+        }
+
         let editor_area = html! {
             <div class="editor-area">
                 <EditorToolbar
                     auto_run={self.auto_run_enabled}
+                    running={self.script_running || self.repl_evaluation_pending()}
+                    stdin_visible={self.stdin_visible}
                     on_run_clicked={ctx.link().callback(|_| Msg::RunButtonClicked)}
+                    on_stop_clicked={ctx.link().callback(|_| Msg::StopButtonClicked)}
                     on_auto_run_clicked={ctx.link().callback(|_| Msg::AutoRunButtonClicked)}
+                    on_stdin_clicked={ctx.link().callback(|_| Msg::StdinButtonClicked)}
                     on_share_clicked={ctx.link().callback(|_| Msg::ShareButtonClicked)}
+                    on_settings_clicked={ctx.link().callback(|_| Msg::SettingsButtonClicked)}
                     on_script_selected={
                         ctx.link().callback(|contents| Msg::ScriptMenuChanged {contents})
                     }
                 />
 
+                <FileTabs
+                    file_names={
+                        self.script_files
+                            .as_ref()
+                            .files()
+                            .iter()
+                            .map(|file| file.name.clone())
+                            .collect::<Vec<_>>()
+                    }
+                    active_file={self.active_file}
+                    on_file_selected={ctx.link().callback(|index| Msg::FileSelected { index })}
+                    on_add_clicked={ctx.link().callback(|_| Msg::AddFileClicked)}
+                    on_remove_clicked={
+                        ctx.link().callback(|index| Msg::RemoveFileClicked { index })
+                    }
+                />
+
                 <Editor
                     on_initialized={ctx.link().callback(|editor| Msg::EditorInitialized {editor})}
                     on_changed={ctx.link().callback(|_| Msg::EditorChanged)}
                 />
+
+                {
+                    if self.stdin_visible {
+                        html! {
+                            <textarea
+                                class="playground-stdin fixed-mono uk-textarea uk-form-small"
+                                placeholder="Input for the script, available via io.stdin"
+                                value={self.script_files.as_ref().stdin().to_string()}
+                                oninput={ctx.link().callback(|event: InputEvent| {
+                                    let input: HtmlTextAreaElement = event.target_unchecked_into();
+                                    Msg::StdinChanged { stdin: input.value() }
+                                })}
+                            ></textarea>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
+            </div>
+        };
+
+        let output_area = html! {
+            <div class="output-area">
+                <OutputToolbar
+                    filter={self.output_filter}
+                    on_filter_changed={
+                        ctx.link().callback(|filter| Msg::OutputFilterChanged { filter })
+                    }
+                />
+
+                <div
+                  ref={self.script_output_ref.clone()}
+                  class={classes!(
+                      "playground-output",
+                      "fixed-mono",
+                      "uk-textarea",
+                      "uk-form-small",
+                      "flex-grow",
+                      self.output_filter.css_class()
+                  )}
+                ></div>
+
+                <StatusBar running={self.script_running} stats={self.execution_stats} />
+
+                <Repl
+                    entries={self.repl_entries.clone()}
+                    on_submit={
+                        ctx.link().callback(|expression| Msg::ReplSubmitted { expression })
+                    }
+                />
             </div>
         };
 
@@ -315,18 +825,14 @@ impl Component for Playground {
                 <div class="playground">
                     { editor_area }
 
-                    <textarea
-                      ref={self.script_output_ref.clone()}
-                      class="playground-output fixed-mono uk-textarea uk-form-small"
-                      readonly=true
-                    ></textarea>
+                    { output_area }
                 </div>
 
                 {
                     if self.show_share_dialog {
                         html! {
                             <Share
-                                script={self.script.as_ref().clone()}
+                                script_files={self.script_files.as_ref().clone()}
                                 on_hidden={ctx.link().callback(|_| Msg::ShareModalClosed)}
                             />
                         }
@@ -334,17 +840,29 @@ impl Component for Playground {
                         html! {}
                     }
                 }
+
+                {
+                    if self.show_settings_dialog {
+                        html! {
+                            <SettingsDialog
+                                settings={self.settings.as_ref().clone()}
+                                on_changed={
+                                    ctx.link().callback(|settings| Msg::SettingsChanged {
+                                        settings
+                                    })
+                                }
+                                on_hidden={ctx.link().callback(|_| Msg::SettingsModalClosed)}
+                            />
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
             </ContextProvider<PlaygroundContext>>
         }
     }
 
-    fn rendered(&mut self, _ctx: &Context<Self>, first_render: bool) {
-        if first_render {
-            let script_output = self.script_output_ref.cast::<Element>().unwrap();
-
-            self.koto = Some(KotoWrapper::new(script_output));
-        }
-
+    fn rendered(&mut self, _ctx: &Context<Self>, _first_render: bool) {
         self.show_share_dialog = false;
     }
 }
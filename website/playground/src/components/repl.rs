@@ -0,0 +1,150 @@
+use {
+    web_sys::{Element, HtmlInputElement},
+    yew::prelude::*,
+};
+
+#[derive(Clone, PartialEq)]
+pub struct ReplEntry {
+    // Identifies the entry in the REPL's results
+    pub id: u32,
+    pub input: String,
+    pub output: String,
+    // The result of the evaluation, or None while the evaluation is in progress
+    pub result: Option<Result<String, String>>,
+}
+
+pub enum Msg {
+    KeyDown(KeyboardEvent),
+}
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub entries: Vec<ReplEntry>,
+    pub on_submit: Callback<String>,
+}
+
+pub struct Repl {
+    input_ref: NodeRef,
+    history_ref: NodeRef,
+    // The index of the history entry that's shown in the input, if any
+    history_index: Option<usize>,
+    // The input that was being edited before navigating through the history
+    draft: String,
+}
+
+impl Repl {
+    fn input(&self) -> HtmlInputElement {
+        self.input_ref
+            .cast::<HtmlInputElement>()
+            .expect("Missing REPL input")
+    }
+}
+
+impl Component for Repl {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            input_ref: NodeRef::default(),
+            history_ref: NodeRef::default(),
+            history_index: None,
+            draft: String::new(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let entries = &ctx.props().entries;
+
+        match msg {
+            Msg::KeyDown(event) => match event.key().as_str() {
+                "Enter" => {
+                    let input = self.input();
+                    let expression = input.value();
+                    if !expression.trim().is_empty() {
+                        ctx.props().on_submit.emit(expression);
+                        input.set_value("");
+                        self.history_index = None;
+                    }
+                    false
+                }
+                "ArrowUp" if !entries.is_empty() => {
+                    event.prevent_default();
+                    let input = self.input();
+                    let index = match self.history_index {
+                        Some(index) => index.saturating_sub(1),
+                        None => {
+                            self.draft = input.value();
+                            entries.len() - 1
+                        }
+                    };
+                    self.history_index = Some(index);
+                    input.set_value(&entries[index].input);
+                    false
+                }
+                "ArrowDown" => {
+                    event.prevent_default();
+                    let input = self.input();
+                    match self.history_index {
+                        Some(index) if index + 1 < entries.len() => {
+                            self.history_index = Some(index + 1);
+                            input.set_value(&entries[index + 1].input);
+                        }
+                        Some(_) => {
+                            self.history_index = None;
+                            input.set_value(&self.draft);
+                        }
+                        None => {}
+                    }
+                    false
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let entries = ctx.props().entries.iter().map(|entry| {
+            let output = if entry.output.is_empty() {
+                html! {}
+            } else {
+                html! { <div>{entry.output.clone()}</div> }
+            };
+
+            let result = match &entry.result {
+                Some(Ok(value)) => html! { <div>{format!("➝ {value}")}</div> },
+                Some(Err(error)) => html! { <div class="uk-text-danger">{error.clone()}</div> },
+                None => html! { <div uk-spinner="ratio: 0.5"></div> },
+            };
+
+            html! {
+                <>
+                    <div class="uk-text-muted">{format!("» {}", entry.input)}</div>
+                    {output}
+                    {result}
+                </>
+            }
+        });
+
+        html! {
+            <div class="repl">
+                <div ref={self.history_ref.clone()} class="repl-history fixed-mono">
+                    { for entries }
+                </div>
+                <input
+                    ref={self.input_ref.clone()}
+                    class="uk-input uk-form-small fixed-mono"
+                    type="text"
+                    placeholder="» Evaluate an expression"
+                    onkeydown={ctx.link().callback(Msg::KeyDown)}
+                />
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, _first_render: bool) {
+        if let Some(history) = self.history_ref.cast::<Element>() {
+            history.set_scroll_top(history.scroll_height());
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use {
+    super::{playground::PlaygroundContext, share::show_modal},
+    crate::{
+        settings::{KeyBindings, Settings},
+        worker_protocol::ExtraLib,
+    },
+    gloo_events::EventListener,
+    web_sys::{Element, HtmlInputElement, HtmlSelectElement},
+    yew::{context::ContextHandle, prelude::*},
+};
+
+pub enum Msg {
+    PlaygroundContextChanged(PlaygroundContext),
+}
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub settings: Settings,
+    pub on_changed: Callback<Settings>,
+    pub on_hidden: Callback<()>,
+}
+
+pub struct SettingsDialog {
+    self_ref: NodeRef,
+    on_hidden_listener: Option<EventListener>,
+    playground_context: PlaygroundContext,
+    _context_listener: ContextHandle<PlaygroundContext>,
+}
+
+impl Component for SettingsDialog {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (playground_context, context_listener) = ctx
+            .link()
+            .context(ctx.link().callback(Msg::PlaygroundContextChanged))
+            .expect("Missing playground context");
+
+        Self {
+            self_ref: NodeRef::default(),
+            on_hidden_listener: None,
+            playground_context,
+            _context_listener: context_listener,
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::PlaygroundContextChanged(context) => {
+                self.playground_context = context;
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let settings = &ctx.props().settings;
+
+        // Creates a callback that emits the settings after they've been modified by `update`
+        let update_setting = |update: fn(&mut Settings, &str)| {
+            let settings = settings.clone();
+            let on_changed = ctx.props().on_changed.clone();
+            Callback::from(move |value: String| {
+                let mut settings = settings.clone();
+                update(&mut settings, &value);
+                on_changed.emit(settings);
+            })
+        };
+
+        let libs = ExtraLib::ALL.into_iter().map(|lib| {
+            let onchange = {
+                let settings = settings.clone();
+                let on_changed = ctx.props().on_changed.clone();
+                Callback::from(move |event: Event| {
+                    let input: HtmlInputElement = event.target_unchecked_into();
+                    let enabled = input.checked();
+                    let mut settings = settings.clone();
+                    // Keep the libs in a consistent order
+                    settings.libs = ExtraLib::ALL
+                        .into_iter()
+                        .filter(|l| {
+                            if *l == lib {
+                                enabled
+                            } else {
+                                settings.libs.contains(l)
+                            }
+                        })
+                        .collect();
+                    on_changed.emit(settings);
+                })
+            };
+
+            html! {
+                <label class="uk-margin-small-right">
+                    <input
+                        class="uk-checkbox uk-margin-small-right"
+                        type="checkbox"
+                        checked={settings.libs.contains(&lib)}
+                        {onchange}
+                    />
+                    {lib.name()}
+                </label>
+            }
+        });
+
+        let key_bindings = KeyBindings::ALL.iter().map(|bindings| {
+            html! {
+                <option
+                    value={bindings.name()}
+                    selected={*bindings == settings.key_bindings}
+                >
+                    {bindings.name()}
+                </option>
+            }
+        });
+
+        let mut dialog_classes = classes![
+            "uk-modal-dialog",
+            "uk-modal-body",
+            "uk-margin-auto-vertical",
+            "uk-border-rounded"
+        ];
+
+        // UIkit doesn't currently have built-in support for inverse colours in modal dialogs
+        if self.playground_context.dark_mode {
+            dialog_classes.push("uk-light");
+            dialog_classes.push("uk-background-secondary");
+        }
+
+        html! {
+            <div uk-modal="" class="uk-flex-top" ref={self.self_ref.clone()}>
+                <div class={dialog_classes}>
+                    <h4 class="uk-modal-title uk-text-lighter">
+                        {"Settings"}
+                    </h4>
+
+                    <form class="uk-form-horizontal">
+                        <h5 class="uk-heading-divider">{"Scripts"}</h5>
+
+                        <NumberSetting
+                            label="Execution limit (seconds)"
+                            value={(settings.execution_limit_ms as f64 / 1000.0).to_string()}
+                            min={0.5}
+                            max={60.0}
+                            step={0.5}
+                            on_changed={update_setting(|settings, value| {
+                                if let Ok(seconds) = value.parse::<f64>() {
+                                    settings.execution_limit_ms =
+                                        (seconds.clamp(0.5, 60.0) * 1000.0) as u64;
+                                }
+                            })}
+                        />
+
+                        <NumberSetting
+                            label="Output limit (KB)"
+                            value={(settings.output_limit / 1000).to_string()}
+                            min={1.0}
+                            max={100_000.0}
+                            step={1.0}
+                            on_changed={update_setting(|settings, value| {
+                                if let Ok(kb) = value.parse::<usize>() {
+                                    settings.output_limit = kb.clamp(1, 100_000) * 1000;
+                                }
+                            })}
+                        />
+
+                        <NumberSetting
+                            label="Auto-run delay (ms)"
+                            value={settings.auto_run_debounce_ms.to_string()}
+                            min={0.0}
+                            max={5000.0}
+                            step={50.0}
+                            on_changed={update_setting(|settings, value| {
+                                if let Ok(ms) = value.parse::<u32>() {
+                                    settings.auto_run_debounce_ms = ms.min(5000);
+                                }
+                            })}
+                        />
+
+                        <div class="uk-margin-small">
+                            <label class="uk-form-label">{"Libs"}</label>
+                            <div class="uk-form-controls uk-form-controls-text">
+                                { for libs }
+                            </div>
+                        </div>
+
+                        <h5 class="uk-heading-divider">{"Editor"}</h5>
+
+                        <NumberSetting
+                            label="Font size"
+                            value={settings.font_size.to_string()}
+                            min={8.0}
+                            max={32.0}
+                            step={1.0}
+                            on_changed={update_setting(|settings, value| {
+                                if let Ok(size) = value.parse::<u16>() {
+                                    settings.font_size = size.clamp(8, 32);
+                                }
+                            })}
+                        />
+
+                        <NumberSetting
+                            label="Tab size"
+                            value={settings.tab_size.to_string()}
+                            min={1.0}
+                            max={8.0}
+                            step={1.0}
+                            on_changed={update_setting(|settings, value| {
+                                if let Ok(size) = value.parse::<u8>() {
+                                    settings.tab_size = size.clamp(1, 8);
+                                }
+                            })}
+                        />
+
+                        <div class="uk-margin-small">
+                            <label class="uk-form-label">{"Key bindings"}</label>
+                            <div class="uk-form-controls">
+                                <select
+                                    class="uk-select uk-form-small"
+                                    onchange={update_setting(|settings, value| {
+                                        if let Some(bindings) = KeyBindings::ALL
+                                            .into_iter()
+                                            .find(|bindings| bindings.name() == value)
+                                        {
+                                            settings.key_bindings = bindings;
+                                        }
+                                    }).reform(|event: Event| {
+                                        let select: HtmlSelectElement =
+                                            event.target_unchecked_into();
+                                        select.value()
+                                    })}
+                                >
+                                    { for key_bindings }
+                                </select>
+                            </div>
+                        </div>
+                    </form>
+
+                    <p class="uk-text-right">
+                        <button class="uk-button uk-button-link uk-modal-close uk-text-capitalize" type="button">
+                            {"Close"}
+                        </button>
+                    </p>
+                </div>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        if first_render {
+            let modal_element = self.self_ref.cast::<Element>().unwrap();
+
+            show_modal(modal_element.clone());
+
+            self.on_hidden_listener = Some(EventListener::new(&modal_element, "hidden", {
+                let on_hidden = ctx.props().on_hidden.clone();
+                move |_| on_hidden.emit(())
+            }));
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct NumberSettingProps {
+    label: &'static str,
+    value: String,
+    min: f64,
+    max: f64,
+    step: f64,
+    on_changed: Callback<String>,
+}
+
+#[function_component(NumberSetting)]
+fn number_setting(props: &NumberSettingProps) -> Html {
+    html! {
+        <div class="uk-margin-small">
+            <label class="uk-form-label">{props.label}</label>
+            <div class="uk-form-controls">
+                <input
+                    class="uk-input uk-form-small uk-form-width-small"
+                    type="number"
+                    value={props.value.clone()}
+                    min={props.min.to_string()}
+                    max={props.max.to_string()}
+                    step={props.step.to_string()}
+                    onchange={props.on_changed.reform(|event: Event| {
+                        let input: HtmlInputElement = event.target_unchecked_into();
+                        input.value()
+                    })}
+                />
+            </div>
+        </div>
+    }
+}
@@ -1,12 +1,13 @@
 use {
     super::playground::PlaygroundContext,
-    crate::{copy_text_to_clipboard, show_notification},
+    crate::{copy_text_to_clipboard, script_files::ScriptFiles, show_notification},
     cloned::cloned,
     gloo_events::EventListener,
     gloo_net::http::Request,
     gloo_utils::window,
     js_sys::encode_uri_component,
-    serde::Deserialize,
+    serde::{Deserialize, Serialize},
+    std::collections::BTreeMap,
     wasm_bindgen::prelude::*,
     web_sys::Element,
     yew::{context::ContextHandle, prelude::*},
@@ -24,7 +25,7 @@ pub enum Msg {
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
-    pub script: String,
+    pub script_files: ScriptFiles,
     pub on_hidden: Callback<()>,
 }
 
@@ -55,16 +56,35 @@ impl Component for Share {
         let origin = location.origin().expect("Missing location origin");
         let path = location.pathname().expect("Missing location pathname");
 
-        let text_url = format!(
-            "{origin}{path}?script={}",
-            encode_uri_component(&ctx.props().script)
-        );
+        let script_files = &ctx.props().script_files;
+        let text_url = if script_files.is_single_file() {
+            let mut url = format!(
+                "{origin}{path}?script={}",
+                encode_uri_component(script_files.main_script())
+            );
+            if !script_files.stdin().is_empty() {
+                url.push_str(&format!(
+                    "&stdin={}",
+                    encode_uri_component(script_files.stdin())
+                ));
+            }
+            url
+        } else {
+            format!(
+                "{origin}{path}?files={}",
+                encode_uri_component(&script_files.to_string())
+            )
+        };
 
         ctx.link().send_future({
-            let script = ctx.props().script.clone();
+            let request = CreateGistRequest {
+                files: script_files.named_files().collect(),
+            };
             async move {
+                let body = serde_json::to_string(&request).expect("Failed to serialize request");
                 match Request::post("/play/create-gist")
-                    .body(&script)
+                    .header("content-type", "application/json")
+                    .body(&body)
                     .send()
                     .await
                 {
@@ -265,6 +285,12 @@ extern "C" {
     pub fn show_modal(element: Element);
 }
 
+#[derive(Serialize)]
+struct CreateGistRequest {
+    // Map of file names to file contents
+    files: BTreeMap<String, String>,
+}
+
 #[derive(Deserialize)]
 pub struct CreateGistResponse {
     id: String,
@@ -0,0 +1,55 @@
+use {crate::worker_protocol::ExecutionStats, yew::prelude::*};
+
+#[derive(PartialEq, Properties)]
+pub struct Props {
+    pub running: bool,
+    // Statistics for the most recent script run
+    pub stats: Option<ExecutionStats>,
+}
+
+#[function_component(StatusBar)]
+pub fn status_bar(props: &Props) -> Html {
+    let status = match (props.running, props.stats) {
+        (true, _) => html! { <span>{"Running…"}</span> },
+        (false, Some(stats)) => {
+            let limit_reached = if stats.execution_limit_reached {
+                Some("Execution limit reached")
+            } else if stats.output_limit_reached {
+                Some("Output limit reached")
+            } else {
+                None
+            };
+            let limit_reached = match limit_reached {
+                Some(message) => html! {
+                    <span class="uk-text-danger uk-margin-small-left">{message}</span>
+                },
+                None => html! {},
+            };
+
+            html! {
+                <>
+                    <span>{format!("Compiled in {}", format_ms(stats.compile_time_ms))}</span>
+                    <span class="uk-margin-small-left">
+                        {format!("Ran in {}", format_ms(stats.run_time_ms))}
+                    </span>
+                    {limit_reached}
+                </>
+            }
+        }
+        (false, None) => html! {},
+    };
+
+    html! {
+        <div class="status-bar uk-text-small uk-text-muted">
+            {status}
+        </div>
+    }
+}
+
+fn format_ms(ms: f64) -> String {
+    if ms < 1000.0 {
+        format!("{ms:.1}ms")
+    } else {
+        format!("{:.2}s", ms / 1000.0)
+    }
+}
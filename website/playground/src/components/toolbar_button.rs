@@ -12,6 +12,8 @@ pub struct Props {
     pub toggle_on_class: &'static str,
     #[prop_or(false)]
     pub toggled: bool,
+    #[prop_or(false)]
+    pub disabled: bool,
     pub on_clicked: Option<Callback<()>>,
     pub uk_toggle: Option<&'static str>,
 }
@@ -85,6 +87,7 @@ pub fn toolbar_button(props: &Props) -> Html {
                 }
             }
             uk-toggle={uk_toggle}
+            disabled={props.disabled}
         >
             {icon_left}
             {caption}
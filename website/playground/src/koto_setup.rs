@@ -0,0 +1,118 @@
+// The configuration of the Koto runtime that runs the playground's scripts
+//
+// This module is also included by the playground's tests so that the examples get run in the same
+// way as they are in the playground, which is why `super` is used rather than `crate` to refer
+// to the protocol types.
+
+use {
+    super::worker_protocol::{ExtraLib, ScriptFile},
+    koto::{
+        bytecode::{
+            compile_script, CompileModuleResult, CompilerSettings, ModuleLoader, ModuleLoaderError,
+            ModuleLoaderErrorKind,
+        },
+        prelude::*,
+        CompileArgs,
+    },
+    std::{cell::RefCell, rc::Rc, time::Duration},
+};
+
+// Makes a Koto runtime with the given libs added to its prelude
+pub fn make_koto(
+    execution_limit: Duration,
+    libs: &[ExtraLib],
+    modules: &ModuleFiles,
+    stdin: impl KotoFile + 'static,
+    stdout: impl KotoFile + 'static,
+    stderr: impl KotoFile + 'static,
+) -> Koto {
+    let koto = Koto::with_settings(
+        KotoSettings::default()
+            .with_execution_limit(execution_limit)
+            .with_module_loader(modules.clone())
+            .with_stdin(stdin)
+            .with_stdout(stdout)
+            .with_stderr(stderr),
+    );
+
+    let prelude = koto.prelude();
+    for lib in libs {
+        let module = match lib {
+            ExtraLib::Color => koto_color::make_module(),
+            ExtraLib::Geometry => koto_geometry::make_module(),
+            ExtraLib::Json => koto_json::make_module(),
+            ExtraLib::Random => koto_random::make_module(),
+            ExtraLib::Regex => koto_regex::make_module(),
+            ExtraLib::Toml => koto_toml::make_module(),
+            ExtraLib::Yaml => koto_yaml::make_module(),
+        };
+        prelude.insert(lib.name(), module);
+    }
+
+    koto
+}
+
+// The arguments for compiling a script with the default compiler settings
+pub fn compile_args(script: &str) -> CompileArgs<'_> {
+    CompileArgs {
+        script,
+        script_path: None,
+        compiler_settings: CompilerSettings::default(),
+    }
+}
+
+// The arguments for compiling an expression in the REPL
+//
+// Top-level values are exported so that they're available in later REPL evaluations.
+pub fn repl_compile_args(expression: &str) -> CompileArgs<'_> {
+    CompileArgs {
+        script: expression,
+        script_path: None,
+        compiler_settings: CompilerSettings {
+            export_top_level_ids: true,
+            ..Default::default()
+        },
+    }
+}
+
+// The project's modules, which are provided to the script's `import` expressions
+//
+// A module is only compiled when it's imported, and the runtime caches the module's exports,
+// so each module is run once as part of the script's run, within the same execution limit.
+#[derive(Clone, Default)]
+pub struct ModuleFiles {
+    files: Rc<RefCell<Vec<ScriptFile>>>,
+}
+
+impl ModuleFiles {
+    pub fn set(&self, files: &[ScriptFile]) {
+        *self.files.borrow_mut() = files.to_vec();
+    }
+}
+
+impl ModuleLoader for ModuleFiles {
+    fn compile_module(
+        &mut self,
+        name: &str,
+        _current_script_path: Option<&KString>,
+        settings: CompilerSettings,
+    ) -> std::result::Result<CompileModuleResult, ModuleLoaderError> {
+        let files = self.files.borrow();
+        let Some(module) = files.iter().find(|module| module.name == name) else {
+            return Err(ModuleLoaderErrorKind::UnableToFindModule(name.into()).into());
+        };
+
+        // The module's name is used as its path so that errors can be traced back to it
+        let path = KString::from(module.name.as_str());
+        let chunk = compile_script(&module.contents, Some(path.clone()), settings)?;
+
+        Ok(CompileModuleResult {
+            chunk,
+            path,
+            loaded_from_cache: false,
+        })
+    }
+
+    // Modules are recompiled for each run, given that they might have been edited
+    fn clear_cache(&mut self) {}
+}
@@ -0,0 +1,116 @@
+use {
+    crate::worker_protocol::{KotoMessage, RunSettings, ScriptFile, WorkerRequest},
+    cloned::cloned,
+    std::{cell::RefCell, collections::VecDeque, rc::Rc},
+    wasm_bindgen::{prelude::*, JsCast},
+    web_sys::{MessageEvent, Worker},
+    yew::Callback,
+};
+
+// The loader script that's generated by Trunk for the worker binary
+//
+// The URL is relative so that versioned snapshots of the playground load their own worker.
+static WORKER_URL: &str = "worker_loader.js";
+
+// Runs Koto scripts in a web worker, forwarding messages from the worker to a callback
+pub struct KotoWorker {
+    worker: Worker,
+    state: Rc<RefCell<WorkerState>>,
+    on_message: Callback<KotoMessage>,
+    _on_message_closure: Closure<dyn FnMut(MessageEvent)>,
+}
+
+#[derive(Default)]
+struct WorkerState {
+    ready: bool,
+    // Requests that were made before the worker was ready, in the order that they were made
+    pending_requests: VecDeque<WorkerRequest>,
+}
+
+impl KotoWorker {
+    pub fn new(on_message: Callback<KotoMessage>) -> Self {
+        let worker = Worker::new(WORKER_URL).expect("Failed to create the Koto worker");
+        let state = Rc::new(RefCell::new(WorkerState::default()));
+
+        let on_message_closure = Closure::wrap(Box::new({
+            cloned!(worker, state, on_message);
+            move |event: MessageEvent| {
+                let Some(data) = event.data().as_string() else {
+                    log::error!("Unexpected message data: {:?}", event.data());
+                    return;
+                };
+
+                match serde_json::from_str(&data) {
+                    Ok(KotoMessage::Ready) => {
+                        let mut state = state.borrow_mut();
+                        state.ready = true;
+                        for request in state.pending_requests.drain(..) {
+                            post_request(&worker, &request);
+                        }
+                    }
+                    Ok(message) => on_message.emit(message),
+                    Err(error) => log::error!("Failed to deserialize Koto message: {error}"),
+                }
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+
+        worker.set_onmessage(Some(on_message_closure.as_ref().unchecked_ref()));
+
+        Self {
+            worker,
+            state,
+            on_message,
+            _on_message_closure: on_message_closure,
+        }
+    }
+
+    pub fn run(
+        &mut self,
+        script: &str,
+        modules: &[ScriptFile],
+        stdin: &str,
+        settings: RunSettings,
+    ) {
+        self.send(WorkerRequest::Run {
+            script: script.into(),
+            modules: modules.to_vec(),
+            stdin: stdin.into(),
+            settings,
+        });
+    }
+
+    pub fn evaluate(&mut self, id: u32, expression: &str, output_limit: usize) {
+        self.send(WorkerRequest::Evaluate {
+            id,
+            expression: expression.into(),
+            output_limit,
+        });
+    }
+
+    // Stops any running script by terminating the worker and starting a new one
+    pub fn stop(&mut self) {
+        *self = Self::new(self.on_message.clone());
+    }
+
+    fn send(&mut self, request: WorkerRequest) {
+        let mut state = self.state.borrow_mut();
+        if state.ready {
+            post_request(&self.worker, &request);
+        } else {
+            state.pending_requests.push_back(request);
+        }
+    }
+}
+
+impl Drop for KotoWorker {
+    fn drop(&mut self) {
+        self.worker.terminate();
+    }
+}
+
+fn post_request(worker: &Worker, request: &WorkerRequest) {
+    let request = serde_json::to_string(request).expect("Failed to serialize worker request");
+    worker
+        .post_message(&JsValue::from_str(&request))
+        .expect("Failed to post worker request");
+}
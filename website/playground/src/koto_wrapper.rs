@@ -1,96 +1,374 @@
 use {
-    koto::{prelude::*, runtime::Result},
-    std::{cell::RefCell, collections::VecDeque, rc::Rc, time::Duration},
-    web_sys::Element,
+    crate::{
+        koto_setup::{compile_args, make_koto, repl_compile_args, ModuleFiles},
+        worker_protocol::{
+            ErrorSpan, ExecutionStats, ExtraLib, KotoMessage, RunSettings, ScriptError, ScriptFile,
+            DEFAULT_EXECUTION_LIMIT_MS,
+        },
+    },
+    instant::Instant,
+    koto::{
+        prelude::*,
+        runtime::{ErrorKind, Result},
+    },
+    std::{cell::RefCell, mem, rc::Rc, time::Duration},
 };
 
-pub type KotoMessageQueue = Rc<RefCell<VecDeque<KotoMessage>>>;
+// How often captured output gets sent to the playground while a script is running
+const OUTPUT_FLUSH_INTERVAL_MS: f64 = 50.0;
 
-pub enum KotoMessage {
-    Print(String),
-}
+type PostMessage = Rc<dyn Fn(KotoMessage)>;
+type SharedOutput = Rc<RefCell<OutputBuffer>>;
+type SharedInput = Rc<RefCell<InputBuffer>>;
 
 pub struct KotoWrapper {
     koto: Koto,
-    script_output: Element,
-    output_buffer: String,
-    message_queue: KotoMessageQueue,
+    output: SharedOutput,
+    input: SharedInput,
+    modules: ModuleFiles,
+    execution_limit: Duration,
+    libs: Vec<ExtraLib>,
+    stats: ExecutionStats,
+    post_message: PostMessage,
 }
 
 impl KotoWrapper {
-    pub fn new(script_output: Element) -> Self {
-        let message_queue = KotoMessageQueue::default();
-
-        let koto = Koto::with_settings(
-            KotoSettings::default()
-                .with_execution_limit(Duration::from_secs(5))
-                .with_stdin(PlaygroundInput {})
-                .with_stdout(OutputCapture {
-                    id: "_stdout_".into(),
-                    queue: message_queue.clone(),
-                })
-                .with_stderr(OutputCapture {
-                    id: "_stderr_".into(),
-                    queue: message_queue.clone(),
-                }),
-        );
+    pub fn new(post_message: impl Fn(KotoMessage) + 'static) -> Self {
+        let post_message: PostMessage = Rc::new(post_message);
+        let output = SharedOutput::new(RefCell::new(OutputBuffer::new(post_message.clone())));
+        let input = SharedInput::default();
+        let modules = ModuleFiles::default();
+        let execution_limit = Duration::from_millis(DEFAULT_EXECUTION_LIMIT_MS);
 
         Self {
-            koto,
-            script_output,
-            output_buffer: String::with_capacity(128),
-            message_queue,
+            koto: create_koto(execution_limit, &[], &output, &input, &modules),
+            output,
+            input,
+            modules,
+            execution_limit,
+            libs: Vec::new(),
+            stats: ExecutionStats::default(),
+            post_message,
         }
     }
 
-    pub fn compile_and_run_script(&mut self, script: &str) {
-        self.script_output.set_inner_html("");
-        self.message_queue.borrow_mut().clear();
+    pub fn compile_and_run_script(
+        &mut self,
+        script: &str,
+        modules: &[ScriptFile],
+        stdin: String,
+        settings: RunSettings,
+    ) {
+        // The execution limit is part of Koto's settings, and the libs are added to the prelude
+        // when the runtime is created, so a new runtime is needed when they change.
+        // The runtime's state is reset for each run anyway.
+        let execution_limit = Duration::from_millis(settings.execution_limit_ms);
+        if execution_limit != self.execution_limit || settings.libs != self.libs {
+            self.koto = create_koto(
+                execution_limit,
+                &settings.libs,
+                &self.output,
+                &self.input,
+                &self.modules,
+            );
+            self.execution_limit = execution_limit;
+            self.libs = settings.libs;
+        }
 
-        self.koto.exports().data_mut().clear();
+        self.output
+            .borrow_mut()
+            .reset(settings.output_limit, OutputMode::Stream);
+        self.input.borrow_mut().reset(stdin);
+        self.stats = ExecutionStats::default();
+
+        // Modules are compiled and run when they're first imported by the script, so the
+        // cached modules from the previous run need to be cleared.
+        self.modules.set(modules);
         self.koto.clear_module_cache();
+        self.koto.exports().data_mut().clear();
+
+        self.run(script);
+
+        (self.post_message)(KotoMessage::Stats(self.stats));
+        (self.post_message)(KotoMessage::Finished);
+    }
 
-        match self.koto.compile(script) {
+    // Evaluates an expression in the context of the last script run
+    //
+    // The script's exports are available to the expression, along with any values that were
+    // assigned in earlier evaluations.
+    pub fn evaluate(&mut self, id: u32, expression: &str, output_limit: usize) {
+        self.output
+            .borrow_mut()
+            .reset(output_limit, OutputMode::Collect);
+
+        let result = match self.koto.compile(repl_compile_args(expression)) {
+            Ok(chunk) => match self.koto.run(chunk) {
+                Ok(value) => self
+                    .koto
+                    .value_to_string(value)
+                    .map_err(|error| error.to_string()),
+                Err(error) => Err(error.to_string()),
+            },
+            Err(error) => Err(error.to_string()),
+        };
+
+        let output = self.output.borrow_mut().take();
+        (self.post_message)(KotoMessage::ReplResult { id, output, result });
+    }
+
+    // Compiles and runs a script
+    fn run(&mut self, script: &str) {
+        let compile_start = Instant::now();
+        let compile_result = self.koto.compile(compile_args(script));
+        self.stats.compile_time_ms = duration_ms(compile_start.elapsed());
+
+        match compile_result {
             Ok(chunk) => {
-                if let Err(e) = self.koto.run(chunk) {
-                    self.process_koto_messages();
-                    return self.error(&e.to_string());
+                let run_start = Instant::now();
+                let result = self.koto.run(chunk);
+                self.stats.run_time_ms = duration_ms(run_start.elapsed());
+                self.output.borrow_mut().flush();
+                // The output limit error could have been caught by the script, so the limit is
+                // checked separately from the run's result
+                self.stats.output_limit_reached = self.output.borrow().limit_reached;
+                if let Err(error) = result {
+                    self.stats.execution_limit_reached = is_timeout(&error);
+                    (self.post_message)(KotoMessage::RuntimeError(script_error(&error)));
                 }
-
-                self.script_output.set_inner_html("");
-                self.process_koto_messages();
             }
             Err(error) => {
-                self.error(&format!("Error while compiling script: {error}"));
+                (self.post_message)(KotoMessage::CompileError(script_error(&error)));
             }
         }
     }
+}
+
+fn create_koto(
+    execution_limit: Duration,
+    libs: &[ExtraLib],
+    output: &SharedOutput,
+    input: &SharedInput,
+    modules: &ModuleFiles,
+) -> Koto {
+    make_koto(
+        execution_limit,
+        libs,
+        modules,
+        PlaygroundInput {
+            input: input.clone(),
+        },
+        OutputCapture {
+            id: "_stdout_".into(),
+            stream: OutputStream::Stdout,
+            output: output.clone(),
+        },
+        OutputCapture {
+            id: "_stderr_".into(),
+            stream: OutputStream::Stderr,
+            output: output.clone(),
+        },
+    )
+}
+
+fn duration_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// Returns true if the script was stopped for exceeding the execution limit
+fn is_timeout(error: &koto::Error) -> bool {
+    match error {
+        koto::Error::RuntimeError(error) => matches!(error.error, ErrorKind::Timeout(_)),
+        _ => false,
+    }
+}
 
-    fn error(&mut self, error: &str) {
-        self.script_output.set_inner_html(error);
-        self.script_output
-            .set_scroll_top(self.script_output.scroll_height());
+fn script_error(error: &koto::Error) -> ScriptError {
+    let (span, module) = error_location(error);
+    let message = match &module {
+        Some(module) => format!("(in module '{module}') {error}"),
+        None => error.to_string(),
+    };
+    ScriptError {
+        message,
+        span,
+        module,
     }
+}
+
+// Returns the location where an error occurred, along with the module that it occurred in
+//
+// Compile errors include the span of the source that failed to compile, and runtime errors
+// include the call stack, with the frame where the error was thrown first.
+// Modules are compiled with their names as their paths, while the main script has no path.
+fn error_location(error: &koto::Error) -> (Option<ErrorSpan>, Option<String>) {
+    let (span, path) = match error {
+        koto::Error::CompileError(error) => (
+            error.source.as_ref().map(|source| source.span),
+            error.source.as_ref().and_then(|source| source.path.clone()),
+        ),
+        koto::Error::RuntimeError(error) => match error.trace.first() {
+            Some(frame) => (
+                frame.chunk.debug_info.get_source_span(frame.instruction),
+                frame.chunk.path.clone(),
+            ),
+            None => (None, None),
+        },
+        _ => (None, None),
+    };
+
+    let span = span.map(|span| ErrorSpan {
+        start_line: span.start.line,
+        start_column: span.start.column,
+        end_line: span.end.line,
+        end_column: span.end.column,
+    });
+
+    (span, path.map(|path| path.to_string()))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputMode {
+    // Output is sent to the playground while the script runs
+    Stream,
+    // Output is collected until it's taken, e.g. for REPL results
+    Collect,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+// Collects output from the script, sending it to the playground in batches
+struct OutputBuffer {
+    buffer: String,
+    mode: OutputMode,
+    // The stream that the buffered output was written to
+    stream: OutputStream,
+    bytes_written: usize,
+    output_limit: usize,
+    limit_reached: bool,
+    last_flush: f64,
+    post_message: PostMessage,
+}
 
-    fn process_koto_messages(&mut self) {
-        for message in self.message_queue.borrow_mut().drain(..) {
-            match message {
-                KotoMessage::Print(s) => self.output_buffer.push_str(&s),
+impl OutputBuffer {
+    fn new(post_message: PostMessage) -> Self {
+        Self {
+            buffer: String::with_capacity(128),
+            mode: OutputMode::Stream,
+            stream: OutputStream::Stdout,
+            bytes_written: 0,
+            output_limit: usize::MAX,
+            limit_reached: false,
+            last_flush: 0.0,
+            post_message,
+        }
+    }
+
+    fn reset(&mut self, output_limit: usize, mode: OutputMode) {
+        self.buffer.clear();
+        self.mode = mode;
+        self.bytes_written = 0;
+        self.output_limit = output_limit;
+        self.limit_reached = false;
+        self.last_flush = js_sys::Date::now();
+    }
+
+    fn push(&mut self, output: &str, stream: OutputStream) -> Result<()> {
+        if stream != self.stream {
+            self.flush();
+            self.stream = stream;
+        }
+
+        let remaining = self.output_limit - self.bytes_written;
+
+        if output.len() > remaining {
+            let mut end = remaining;
+            while !output.is_char_boundary(end) {
+                end -= 1;
             }
+            self.buffer.push_str(&output[..end]);
+            self.bytes_written = self.output_limit;
+            self.limit_reached = true;
+            self.flush();
+
+            return runtime_error!(
+                "The output limit of {} bytes has been reached",
+                self.output_limit
+            );
         }
 
-        if !self.output_buffer.is_empty() {
-            self.script_output
-                .append_with_str_1(&self.output_buffer)
-                .unwrap();
-            self.script_output
-                .set_scroll_top(self.script_output.scroll_height());
-            self.output_buffer.clear();
+        self.buffer.push_str(output);
+        self.bytes_written += output.len();
+
+        if js_sys::Date::now() - self.last_flush >= OUTPUT_FLUSH_INTERVAL_MS {
+            self.flush();
         }
+
+        Ok(())
+    }
+
+    fn take(&mut self) -> String {
+        mem::take(&mut self.buffer)
+    }
+
+    fn flush(&mut self) {
+        if self.mode == OutputMode::Collect {
+            return;
+        }
+
+        if !self.buffer.is_empty() {
+            let output = mem::take(&mut self.buffer);
+            (self.post_message)(match self.stream {
+                OutputStream::Stdout => KotoMessage::Print(output),
+                OutputStream::Stderr => KotoMessage::Stderr(output),
+            });
+        }
+        self.last_flush = js_sys::Date::now();
     }
 }
 
-struct PlaygroundInput {}
+// The input that's provided to the script via stdin
+#[derive(Default)]
+struct InputBuffer {
+    contents: String,
+    // The position in the contents of the next read
+    position: usize,
+}
+
+impl InputBuffer {
+    fn reset(&mut self, contents: String) {
+        self.contents = contents;
+        self.position = 0;
+    }
+
+    fn read_line(&mut self) -> Option<String> {
+        let remaining = &self.contents[self.position..];
+        if remaining.is_empty() {
+            return None;
+        }
+
+        let line_end = remaining
+            .find('\n')
+            .map_or(remaining.len(), |index| index + 1);
+        self.position += line_end;
+        Some(remaining[..line_end].to_string())
+    }
+
+    fn read_to_string(&mut self) -> String {
+        let result = self.contents[self.position..].to_string();
+        self.position = self.contents.len();
+        result
+    }
+}
+
+// Provides the playground's input panel contents to the script as stdin
+struct PlaygroundInput {
+    input: SharedInput,
+}
 
 impl KotoFile for PlaygroundInput {
     fn id(&self) -> KString {
@@ -101,18 +379,19 @@ impl KotoFile for PlaygroundInput {
 impl KotoWrite for PlaygroundInput {}
 impl KotoRead for PlaygroundInput {
     fn read_line(&self) -> Result<Option<String>> {
-        runtime_error!("stdin is unsupported in the browser")
+        Ok(self.input.borrow_mut().read_line())
     }
 
     fn read_to_string(&self) -> Result<String> {
-        runtime_error!("stdin is unsupported in the browser")
+        Ok(self.input.borrow_mut().read_to_string())
     }
 }
 
-// Captures output from Koto in a String
+// Captures output from Koto in the shared output buffer
 struct OutputCapture {
     id: KString,
-    queue: KotoMessageQueue,
+    stream: OutputStream,
+    output: SharedOutput,
 }
 
 impl KotoFile for OutputCapture {
@@ -128,20 +407,17 @@ impl KotoWrite for OutputCapture {
             Ok(s) => s,
             Err(e) => return Err(e.to_string().into()),
         };
-        self.queue
-            .borrow_mut()
-            .push_back(KotoMessage::Print(bytes_str.to_string()));
-        Ok(())
+        self.output.borrow_mut().push(bytes_str, self.stream)
     }
 
     fn write_line(&self, output: &str) -> Result<()> {
-        self.queue
+        self.output
             .borrow_mut()
-            .push_back(KotoMessage::Print(format!("{output}\n")));
-        Ok(())
+            .push(&format!("{output}\n"), self.stream)
     }
 
     fn flush(&self) -> Result<()> {
+        self.output.borrow_mut().flush();
         Ok(())
     }
 }
@@ -1,14 +1,17 @@
 mod ace_bindings;
 mod components;
-mod koto_wrapper;
+mod koto_worker;
+mod script_files;
+mod settings;
 mod stored_value;
+mod worker_protocol;
 
 use {
     components::playground::Playground,
     gloo_utils::{body, document, window},
     js_sys::encode_uri_component,
     wasm_bindgen::prelude::*,
-    web_sys::console,
+    web_sys::{console, UrlSearchParams},
     yew::prelude::*,
 };
 
@@ -22,7 +25,25 @@ fn main() {
         .get_element_by_id("playground-wrapper")
         .expect("Missing playground wrapper");
 
-    yew::start_app_in_element::<App>(playground_wrapper);
+    // The playground can be embedded in other pages (e.g. the docs) with `?mode=embed`
+    let embedded = UrlSearchParams::new_with_str(
+        &window()
+            .location()
+            .search()
+            .expect("Missing location search string"),
+    )
+    .ok()
+    .and_then(|params| params.get("mode"))
+    .is_some_and(|mode| mode == "embed");
+    if embedded {
+        document()
+            .document_element()
+            .expect("Missing document element")
+            .set_attribute("playground-mode", "embed")
+            .expect("Failed to set playground mode");
+    }
+
+    yew::start_app_with_props_in_element::<App>(playground_wrapper, AppProps { embedded });
 }
 
 #[wasm_bindgen(module = "/src/koto-highlight-rules.js")]
@@ -40,11 +61,16 @@ extern "C" {
     fn copy_text_to_clipboard(text: &str);
 }
 
+#[derive(PartialEq, Properties)]
+struct AppProps {
+    embedded: bool,
+}
+
 #[function_component(App)]
-fn app() -> Html {
+fn app(props: &AppProps) -> Html {
     html! {
         <div class="container">
-            <Playground />
+            <Playground embedded={props.embedded} />
         </div>
     }
 }
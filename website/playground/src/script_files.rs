@@ -0,0 +1,189 @@
+use {
+    crate::worker_protocol::ScriptFile,
+    serde::{Deserialize, Serialize},
+    std::{fmt, str::FromStr},
+};
+
+// The scripts that make up a playground project
+//
+// The first file is the main script, the other files can be imported by the main script (or by
+// each other) as modules, using their names.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptFiles {
+    files: Vec<ScriptFile>,
+    // The input that's provided to the main script via stdin
+    #[serde(default)]
+    stdin: String,
+}
+
+impl ScriptFiles {
+    pub const MAIN_SCRIPT: &'static str = "main";
+    // The name used for the stdin contents when sharing the project as a set of files
+    pub const STDIN_FILE: &'static str = "stdin.txt";
+    // Gists can't contain empty files, so empty scripts are shared with placeholder contents
+    const EMPTY_SCRIPT_PLACEHOLDER: &'static str = "# This script is empty\n";
+
+    pub fn new(main_script: &str) -> Self {
+        Self {
+            files: vec![ScriptFile {
+                name: Self::MAIN_SCRIPT.into(),
+                contents: main_script.into(),
+            }],
+            stdin: String::new(),
+        }
+    }
+
+    // Creates a project from a set of named files, e.g. from a gist
+    //
+    // The main script is taken from `main.koto` if it's present, otherwise the first file is used
+    // as the main script (gists created by previous versions of the playground contain a single
+    // file named `playground.koto`). Files other than `.koto` scripts and the stdin file are
+    // ignored, e.g. a README that was added to the gist.
+    pub fn from_named_files(files: impl IntoIterator<Item = (String, String)>) -> Option<Self> {
+        let mut stdin = String::new();
+        let mut files = files
+            .into_iter()
+            .filter_map(|(file_name, contents)| {
+                if file_name == Self::STDIN_FILE {
+                    stdin = contents;
+                    return None;
+                }
+
+                let name = file_name.strip_suffix(".koto")?;
+                let contents = if contents == Self::EMPTY_SCRIPT_PLACEHOLDER {
+                    String::new()
+                } else {
+                    contents
+                };
+                Some(ScriptFile {
+                    name: name.to_string(),
+                    contents,
+                })
+            })
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            return None;
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let main_index = files
+            .iter()
+            .position(|file| file.name == Self::MAIN_SCRIPT)
+            .unwrap_or(0);
+        let mut main = files.remove(main_index);
+        main.name = Self::MAIN_SCRIPT.into();
+        files.insert(0, main);
+
+        Some(Self { files, stdin })
+    }
+
+    // Returns the project's files as pairs of file names and contents, for sharing as a gist
+    //
+    // Empty scripts are given placeholder contents, which are removed when the gist is loaded.
+    pub fn named_files(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        let stdin = if self.stdin.is_empty() {
+            None
+        } else {
+            Some((Self::STDIN_FILE.to_string(), self.stdin.clone()))
+        };
+
+        self.files
+            .iter()
+            .map(|file| {
+                let contents = if file.contents.is_empty() {
+                    Self::EMPTY_SCRIPT_PLACEHOLDER.to_string()
+                } else {
+                    file.contents.clone()
+                };
+                (format!("{}.koto", file.name), contents)
+            })
+            .chain(stdin)
+    }
+
+    pub fn files(&self) -> &[ScriptFile] {
+        &self.files
+    }
+
+    pub fn main_script(&self) -> &str {
+        &self.files[0].contents
+    }
+
+    pub fn modules(&self) -> &[ScriptFile] {
+        &self.files[1..]
+    }
+
+    pub fn contents(&self, index: usize) -> &str {
+        &self.files[index].contents
+    }
+
+    pub fn set_contents(&mut self, index: usize, contents: String) {
+        self.files[index].contents = contents;
+    }
+
+    pub fn stdin(&self) -> &str {
+        &self.stdin
+    }
+
+    pub fn set_stdin(&mut self, stdin: String) {
+        self.stdin = stdin;
+    }
+
+    pub fn is_single_file(&self) -> bool {
+        self.files.len() == 1
+    }
+
+    // Adds a new empty module, returning its index
+    pub fn add_module(&mut self, name: &str) -> Result<usize, String> {
+        let name = name.trim().strip_suffix(".koto").unwrap_or(name.trim());
+
+        let valid_name = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(format!("'{name}' isn't a valid module name"));
+        }
+        if self.files.iter().any(|file| file.name == name) {
+            return Err(format!("A file named '{name}' already exists"));
+        }
+
+        self.files.push(ScriptFile {
+            name: name.into(),
+            contents: String::new(),
+        });
+        Ok(self.files.len() - 1)
+    }
+
+    // Removes a module, the main script can't be removed
+    pub fn remove_module(&mut self, index: usize) {
+        if index > 0 {
+            self.files.remove(index);
+        }
+    }
+}
+
+impl Default for ScriptFiles {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl fmt::Display for ScriptFiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+impl FromStr for ScriptFiles {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let result: Self = serde_json::from_str(s)?;
+        if result.files.is_empty() {
+            return Err(serde::de::Error::custom("missing main script"));
+        }
+        Ok(result)
+    }
+}
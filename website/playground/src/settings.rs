@@ -0,0 +1,124 @@
+use {
+    crate::{
+        stored_value::StoredValue,
+        worker_protocol::{
+            ExtraLib, RunSettings, DEFAULT_EXECUTION_LIMIT_MS, DEFAULT_OUTPUT_LIMIT,
+        },
+    },
+    serde::{Deserialize, Serialize},
+    std::{fmt, str::FromStr},
+};
+
+// The playground's user settings, edited via the settings dialog
+//
+// Missing fields fall back to their defaults, so that settings saved by earlier versions of the
+// playground can still be loaded.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub execution_limit_ms: u64,
+    pub output_limit: usize,
+    pub font_size: u16,
+    pub tab_size: u8,
+    pub key_bindings: KeyBindings,
+    // How long to wait after an edit before automatically running the script
+    pub auto_run_debounce_ms: u32,
+    // The extra libs that are available to scripts
+    pub libs: Vec<ExtraLib>,
+}
+
+impl Settings {
+    // Loads the stored settings
+    //
+    // Earlier versions of the playground stored the vim bindings setting separately, so it's used
+    // as the default if no settings have been saved yet.
+    pub fn load() -> StoredValue<Self> {
+        StoredValue::new_with_default("settings", || {
+            let vim_bindings_enabled = StoredValue::<bool>::new("vim-bindings-enabled");
+            Self {
+                key_bindings: if vim_bindings_enabled.get() {
+                    KeyBindings::Vim
+                } else {
+                    KeyBindings::Default
+                },
+                ..Default::default()
+            }
+        })
+    }
+
+    pub fn run_settings(&self) -> RunSettings {
+        RunSettings {
+            output_limit: self.output_limit,
+            execution_limit_ms: self.execution_limit_ms,
+            libs: self.libs.clone(),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            execution_limit_ms: DEFAULT_EXECUTION_LIMIT_MS,
+            output_limit: DEFAULT_OUTPUT_LIMIT,
+            font_size: 14,
+            tab_size: 2,
+            key_bindings: KeyBindings::Default,
+            auto_run_debounce_ms: 300,
+            libs: ExtraLib::ALL.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+impl FromStr for Settings {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyBindings {
+    #[default]
+    Default,
+    Vim,
+    Emacs,
+}
+
+impl KeyBindings {
+    pub const ALL: [Self; 3] = [Self::Default, Self::Vim, Self::Emacs];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Default => "Default",
+            Self::Vim => "Vim",
+            Self::Emacs => "Emacs",
+        }
+    }
+
+    // The name of the Ace keyboard handler that provides the bindings
+    pub fn ace_handler(&self) -> &'static str {
+        match self {
+            Self::Default => "",
+            Self::Vim => "ace/keyboard/vim",
+            Self::Emacs => "ace/keyboard/emacs",
+        }
+    }
+
+    // Parses the value of the document's `editor-bindings` attribute
+    pub fn from_attribute(value: &str) -> Option<Self> {
+        match value {
+            "default" => Some(Self::Default),
+            "vim" => Some(Self::Vim),
+            "emacs" => Some(Self::Emacs),
+            _ => None,
+        }
+    }
+}
@@ -10,6 +10,8 @@ impl<T: Default + Display + FromStr> StorableValue for T {}
 pub struct StoredValue<T: StorableValue> {
     value: T,
     name: String,
+    // False for values that are neither loaded from nor saved to local storage
+    persistent: bool,
 }
 
 impl<T: StorableValue> StoredValue<T> {
@@ -25,6 +27,16 @@ impl<T: StorableValue> StoredValue<T> {
         Self {
             value,
             name: name.into(),
+            persistent: true,
+        }
+    }
+
+    // Makes a value that's only kept in memory, leaving any stored value untouched
+    pub fn temporary(value: T) -> Self {
+        Self {
+            value,
+            name: String::new(),
+            persistent: false,
         }
     }
 
@@ -32,11 +44,18 @@ impl<T: StorableValue> StoredValue<T> {
         &self.value
     }
 
+    pub fn as_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
     pub fn set(&mut self, value: T) {
         self.value = value;
     }
 
     pub fn save(&self) {
+        if !self.persistent {
+            return;
+        }
         set_local_storage_value(&self.name, &self.value.to_string());
     }
 }
@@ -0,0 +1,62 @@
+// The entry point for the playground's web worker
+//
+// Scripts are run in the worker so that long-running scripts don't block the editor.
+
+mod koto_setup;
+mod koto_wrapper;
+mod worker_protocol;
+
+use {
+    cloned::cloned,
+    koto_wrapper::KotoWrapper,
+    wasm_bindgen::{prelude::*, JsCast},
+    web_sys::{DedicatedWorkerGlobalScope, MessageEvent},
+    worker_protocol::{KotoMessage, WorkerRequest},
+};
+
+fn main() {
+    console_error_panic_hook::set_once();
+    wasm_logger::init(wasm_logger::Config::default());
+
+    let scope = js_sys::global().unchecked_into::<DedicatedWorkerGlobalScope>();
+
+    let mut koto = KotoWrapper::new({
+        cloned!(scope);
+        move |message| post_message(&scope, &message)
+    });
+
+    let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+        let Some(data) = event.data().as_string() else {
+            log::error!("Unexpected message data: {:?}", event.data());
+            return;
+        };
+
+        match serde_json::from_str(&data) {
+            Ok(WorkerRequest::Run {
+                script,
+                modules,
+                stdin,
+                settings,
+            }) => koto.compile_and_run_script(&script, &modules, stdin, settings),
+            Ok(WorkerRequest::Evaluate {
+                id,
+                expression,
+                output_limit,
+            }) => koto.evaluate(id, &expression, output_limit),
+            Err(error) => log::error!("Failed to deserialize worker request: {error}"),
+        }
+    }) as Box<dyn FnMut(MessageEvent)>);
+
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // The worker lives for as long as the closure is needed
+    on_message.forget();
+
+    post_message(&scope, &KotoMessage::Ready);
+}
+
+fn post_message(scope: &DedicatedWorkerGlobalScope, message: &KotoMessage) {
+    let message = serde_json::to_string(message).expect("Failed to serialize Koto message");
+    scope
+        .post_message(&JsValue::from_str(&message))
+        .expect("Failed to post Koto message");
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+// Requests sent from the playground to the Koto worker
+#[derive(Serialize, Deserialize)]
+pub enum WorkerRequest {
+    Run {
+        script: String,
+        // Modules that can be imported by the script
+        modules: Vec<ScriptFile>,
+        // The input that the script can read from stdin
+        stdin: String,
+        settings: RunSettings,
+    },
+    // Evaluates an expression in the REPL, using the state left by the last script run
+    //
+    // The runtime from the last run is reused, so only the output limit can be applied.
+    Evaluate {
+        // The id of the REPL entry, which is included in the result
+        id: u32,
+        expression: String,
+        output_limit: usize,
+    },
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptFile {
+    // The file's name without the `.koto` extension, which is also its module name
+    pub name: String,
+    pub contents: String,
+}
+
+// Settings that are applied when running a script
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RunSettings {
+    // The maximum number of bytes of output that a script can produce
+    pub output_limit: usize,
+    // How long a script can run for before it gets stopped
+    pub execution_limit_ms: u64,
+    // The extra libs that are added to the prelude
+    pub libs: Vec<ExtraLib>,
+}
+
+pub const DEFAULT_OUTPUT_LIMIT: usize = 1_000_000;
+pub const DEFAULT_EXECUTION_LIMIT_MS: u64 = 5_000;
+
+// Libs from Koto's `libs` crates that are available in the playground
+//
+// The `tempfile` lib isn't included given that it needs access to the filesystem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExtraLib {
+    Color,
+    Geometry,
+    Json,
+    Random,
+    Regex,
+    Toml,
+    Yaml,
+}
+
+impl ExtraLib {
+    pub const ALL: [Self; 7] = [
+        Self::Color,
+        Self::Geometry,
+        Self::Json,
+        Self::Random,
+        Self::Regex,
+        Self::Toml,
+        Self::Yaml,
+    ];
+
+    // The name of the lib's module in the prelude
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Color => "color",
+            Self::Geometry => "geometry",
+            Self::Json => "json",
+            Self::Random => "random",
+            Self::Regex => "regex",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+        }
+    }
+}
+
+// Messages sent from the Koto worker back to the playground
+#[derive(Serialize, Deserialize)]
+pub enum KotoMessage {
+    // The worker has been initialized and is ready to receive requests
+    Ready,
+    // Output written to stdout
+    Print(String),
+    // Output written to stderr
+    Stderr(String),
+    CompileError(ScriptError),
+    RuntimeError(ScriptError),
+    // The result of a REPL evaluation, along with any output that was produced
+    ReplResult {
+        // The id of the REPL entry that was evaluated
+        id: u32,
+        output: String,
+        result: Result<String, String>,
+    },
+    // Statistics for the script run, sent before the run is finished
+    Stats(ExecutionStats),
+    // The script has finished running
+    //
+    // REPL evaluations don't send this, they're finished when their result is sent.
+    Finished,
+}
+
+// An error that occurred while compiling or running a script
+#[derive(Serialize, Deserialize)]
+pub struct ScriptError {
+    pub message: String,
+    // The location in the script where the error occurred, if available
+    pub span: Option<ErrorSpan>,
+    // The module where the error occurred, or None if the error is in the main script
+    pub module: Option<String>,
+}
+
+// A zero-based line/column range in a script
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ErrorSpan {
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+// Timing information for a script run, and whether or not the run was stopped by a limit
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionStats {
+    // The time taken to compile the main script
+    pub compile_time_ms: f64,
+    // The time taken to run the script, imported modules are compiled and run when they're first
+    // imported so their compile times are included here
+    pub run_time_ms: f64,
+    pub execution_limit_reached: bool,
+    pub output_limit_reached: bool,
+}
@@ -0,0 +1,91 @@
+// The playground's runtime setup is shared with the tests, not all of it is used here
+#[allow(dead_code)]
+#[path = "../../src/koto_setup.rs"]
+mod koto_setup;
+#[allow(dead_code)]
+#[path = "../../src/worker_protocol.rs"]
+mod worker_protocol;
+
+use koto::{prelude::*, PtrMut};
+use koto_setup::{compile_args, ModuleFiles};
+use std::time::Duration;
+use worker_protocol::{ExtraLib, DEFAULT_EXECUTION_LIMIT_MS};
+
+// Makes a Koto runtime that's configured with the playground's default settings
+//
+// The script's stdout and stderr output is captured in `output`.
+pub fn make_koto(output: &PtrMut<String>) -> Koto {
+    koto_setup::make_koto(
+        Duration::from_millis(DEFAULT_EXECUTION_LIMIT_MS),
+        &ExtraLib::ALL,
+        &ModuleFiles::default(),
+        EmptyInput,
+        OutputCapture {
+            output: output.clone(),
+        },
+        OutputCapture {
+            output: output.clone(),
+        },
+    )
+}
+
+// Compiles and runs a script in the same way as the playground
+pub fn run_script(koto: &mut Koto, script: &str) -> koto::Result<KValue> {
+    let chunk = koto.compile(compile_args(script))?;
+    koto.run(chunk)
+}
+
+// The playground doesn't provide any input unless it's been added by the user
+#[derive(Debug)]
+struct EmptyInput;
+
+impl KotoFile for EmptyInput {
+    fn id(&self) -> KString {
+        "_stdin_".into()
+    }
+}
+
+impl KotoWrite for EmptyInput {}
+impl KotoRead for EmptyInput {
+    fn read_line(&self) -> koto::runtime::Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn read_to_string(&self) -> koto::runtime::Result<String> {
+        Ok(String::new())
+    }
+}
+
+#[derive(Debug)]
+struct OutputCapture {
+    output: PtrMut<String>,
+}
+
+impl KotoFile for OutputCapture {
+    fn id(&self) -> KString {
+        "_stdout_".into()
+    }
+}
+
+impl KotoRead for OutputCapture {}
+impl KotoWrite for OutputCapture {
+    fn write(&self, bytes: &[u8]) -> koto::runtime::Result<()> {
+        let bytes_str = match std::str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => return Err(e.to_string().into()),
+        };
+        self.output.borrow_mut().push_str(bytes_str);
+        Ok(())
+    }
+
+    fn write_line(&self, output: &str) -> koto::runtime::Result<()> {
+        let mut unlocked = self.output.borrow_mut();
+        unlocked.push_str(output);
+        unlocked.push('\n');
+        Ok(())
+    }
+
+    fn flush(&self) -> koto::runtime::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use anyhow::{bail, Context, Result};
+use glob::glob;
+use koto::PtrMut;
+use std::fs;
+
+// Runs the scripts from the docs' playground links, using the same configuration as the
+// playground's KotoWrapper
+#[test]
+fn run_docs_playground_links() -> Result<()> {
+    let mut docs = Vec::new();
+    for path in glob("../content/docs/next/**/*.md").context("failed to scan for docs")? {
+        docs.push(path.context("failed to read path")?);
+    }
+
+    let mut examples = Vec::new();
+    for path in docs {
+        let doc =
+            fs::read_to_string(&path).with_context(|| format!("failed to read '{path:?}'"))?;
+        examples.extend(
+            extract_playground_links(&doc)
+                .into_iter()
+                .map(|(heading, script)| (path.clone(), heading, script)),
+        );
+    }
+
+    // The docs are generated by `cargo xtask docs`, which `just test` runs before the tests
+    if examples.is_empty() {
+        bail!("No playground links found in content/docs/next, run `cargo xtask docs` first");
+    }
+
+    let mut failures = Vec::new();
+    for (path, heading, script) in examples {
+        if let Err(e) = run_script(&script) {
+            failures.push(format!("{path:?} - '{heading}'\n  -> {e}"));
+        }
+    }
+
+    if !failures.is_empty() {
+        bail!(
+            "{} playground link(s) failed to run:\n\n{}",
+            failures.len(),
+            failures.join("\n\n")
+        );
+    }
+
+    Ok(())
+}
+
+// Returns the script contained in each playground link shortcode, along with the heading of the
+// section that contains it
+fn extract_playground_links(doc: &str) -> Vec<(String, String)> {
+    let mut result = Vec::new();
+    let mut heading = String::new();
+    let mut in_code_block = false;
+    let mut script = None;
+
+    for line in doc.lines() {
+        if let Some(mut current) = script.take() {
+            if line.trim() == "{% end %}" {
+                result.push((heading.clone(), current));
+            } else {
+                current.push_str(line);
+                current.push('\n');
+                script = Some(current);
+            }
+        } else if line.starts_with("{% example_playground_link(") {
+            script = Some(String::new());
+        } else if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block && line.starts_with('#') {
+            heading = line.trim_start_matches('#').trim().to_string();
+        }
+    }
+
+    result
+}
+
+fn run_script(script: &str) -> Result<()> {
+    let output = PtrMut::from(String::new());
+    let mut koto = common::make_koto(&output);
+
+    match common::run_script(&mut koto, script) {
+        Ok(_) => Ok(()),
+        Err(e) => bail!("{e}\n\n{script}"),
+    }
+}
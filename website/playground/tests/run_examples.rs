@@ -1,74 +1,70 @@
+mod common;
+
 use anyhow::{bail, Context, Result};
 use glob::glob;
-use koto::{prelude::*, PtrMut};
-use std::fs;
+use koto::PtrMut;
+use similar::TextDiff;
+use std::{env, fs, path::Path};
+
+// Set this environment variable to write the examples' output to their snapshot files
+const BLESS_VAR: &str = "BLESS_SNAPSHOTS";
 
 #[test]
 fn run_playground_examples() -> Result<()> {
     let mut found_script = false;
-
-    let output = PtrMut::from(String::new());
-
-    let mut koto = Koto::with_settings(
-        KotoSettings::default()
-            .with_stdout(OutputCapture {
-                output: output.clone(),
-            })
-            .with_stderr(OutputCapture {
-                output: output.clone(),
-            }),
-    );
+    let mut failures = Vec::new();
 
     for path in glob("**/*.koto").context("failed to scan for scripts")? {
         let path = path.context("failed to read path")?;
-        let script = fs::read_to_string(&path)
-            .with_context(|| format!("failed to read from path '{path:?}'"))?;
+        found_script = true;
 
-        if let Err(e) = koto.compile_and_run(&script) {
-            bail!(
-                "error while running script '{path:?}'\n  -> {}",
-                e.to_string()
-            );
+        if let Err(e) = run_example(&path) {
+            failures.push(format!("{path:?}\n  -> {e}"));
         }
-
-        found_script = true;
     }
 
     assert!(found_script);
 
-    Ok(())
-}
+    if !failures.is_empty() {
+        bail!(
+            "{} example(s) failed:\n\n{}",
+            failures.len(),
+            failures.join("\n\n")
+        );
+    }
 
-#[derive(Debug)]
-struct OutputCapture {
-    output: PtrMut<String>,
+    Ok(())
 }
 
-impl KotoFile for OutputCapture {
-    fn id(&self) -> KString {
-        "_stdout_".into()
-    }
-}
+// Runs the example in a fresh runtime, and then checks its output against its snapshot
+fn run_example(path: &Path) -> Result<()> {
+    let script =
+        fs::read_to_string(path).with_context(|| format!("failed to read from path '{path:?}'"))?;
 
-impl KotoRead for OutputCapture {}
-impl KotoWrite for OutputCapture {
-    fn write(&self, bytes: &[u8]) -> koto::runtime::Result<()> {
-        let bytes_str = match std::str::from_utf8(bytes) {
-            Ok(s) => s,
-            Err(e) => return Err(e.to_string().into()),
-        };
-        self.output.borrow_mut().push_str(bytes_str);
-        Ok(())
+    let output = PtrMut::from(String::new());
+    let mut koto = common::make_koto(&output);
+    if let Err(e) = common::run_script(&mut koto, &script) {
+        bail!("error while running script: {e}");
     }
 
-    fn write_line(&self, output: &str) -> koto::runtime::Result<()> {
-        let mut unlocked = self.output.borrow_mut();
-        unlocked.push_str(output);
-        unlocked.push('\n');
-        Ok(())
+    // The expected output is stored next to the script, e.g. `fizz_buzz.koto.out`
+    let snapshot_path = path.with_extension("koto.out");
+    let actual = output.borrow().clone();
+    if env::var_os(BLESS_VAR).is_some() {
+        fs::write(&snapshot_path, &actual)
+            .with_context(|| format!("failed to write snapshot '{snapshot_path:?}'"))?;
+    } else {
+        let expected = fs::read_to_string(&snapshot_path).with_context(|| {
+            format!("failed to read snapshot '{snapshot_path:?}', run with {BLESS_VAR}=1")
+        })?;
+        if actual != expected {
+            let diff = TextDiff::from_lines(&expected, &actual)
+                .unified_diff()
+                .header("expected", "actual")
+                .to_string();
+            bail!("output mismatch\n\n{diff}\nrun with {BLESS_VAR}=1 to update the snapshot");
+        }
     }
 
-    fn flush(&self) -> koto::runtime::Result<()> {
-        Ok(())
-    }
+    Ok(())
 }
@@ -1,58 +1,174 @@
 use std::{
-    collections::HashSet, ffi::OsStr, fs, iter::Peekable, mem, os::unix::fs::MetadataExt,
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ffi::OsStr,
+    fs,
+    iter::Peekable,
+    mem,
     path::Path,
 };
 
 use anyhow::{bail, Context, Result};
 use pulldown_cmark::{HeadingLevel, Parser};
 use serde::Serialize;
-use sluggify::sluggify::sluggify;
+use slug::slugify;
 
-pub fn run() -> Result<()> {
+use crate::{check_outputs, docs_manifest::DocsManifest};
+
+// Builds the search index, or with `check` set, checks that the index is up to date without
+// writing anything
+pub fn run(check: bool) -> Result<()> {
     let index = IndexBuilder::build_index()?;
+    let search_index = SearchIndex::new(&index.entries);
 
     let index_json =
-        serde_json::to_string(&index.entries).context("failed to serialize the index as JSON")?;
+        serde_json::to_string(&search_index).context("failed to serialize the index as JSON")?;
+    let manifest_json = manifest_json()?;
+
+    let index_path = "static/search-index-next.json";
+    if check {
+        return check_outputs::check(
+            [
+                (Path::new(index_path), index_json.as_str()),
+                (Path::new(MANIFEST_PATH), manifest_json.as_str()),
+            ],
+            [],
+            "search-index",
+        );
+    }
+
+    fs::write(index_path, &index_json)
+        .with_context(|| format!("failed to write '{index_path}'"))?;
+    fs::write(MANIFEST_PATH, manifest_json)
+        .with_context(|| format!("failed to write '{MANIFEST_PATH}'"))?;
 
-    let output_path = "static/search-index-next.json";
-    fs::write(output_path, index_json)?;
-    let output_size = (fs::metadata(output_path)?.size() as f64 / 1000.0).round();
+    // The raw entries were previously used as the index, they're serialized here for comparison
+    let entries_json =
+        serde_json::to_string(&index.entries).context("failed to serialize the entries as JSON")?;
 
+    let index_size = index_json.len() as f64 / 1000.0;
+    let entries_size = entries_json.len() as f64 / 1000.0;
     println!(
-        "Search index built ({} entries, {output_size}kB)",
-        index.entries.len(),
+        "Search index built ({} entries, {} terms)
+  Raw entries:       {entries_size:.1}kB
+  Precomputed index: {index_size:.1}kB ({:.0}% of the raw entries)",
+        search_index.docs.len(),
+        search_index.terms.len(),
+        index_size / entries_size * 100.0,
     );
 
     Ok(())
 }
 
+// The manifest listing the search indexes, which is used when searching across all versions of
+// the docs
+const MANIFEST_PATH: &str = "static/search-indexes.json";
+
+// The folder containing the versions of the docs, with the upcoming version in 'next'
+const DOCS_VERSIONS_DIR: &str = "content/docs";
+
+// The first version of the docs that was released with a search index
+const FIRST_INDEXED_VERSION: &[u64] = &[0, 15];
+
+// Writes the manifest of search indexes, e.g. after a new version's index has been added
+pub fn write_manifest() -> Result<()> {
+    fs::write(MANIFEST_PATH, manifest_json()?)
+        .with_context(|| format!("failed to write '{MANIFEST_PATH}'"))?;
+    println!("Search index manifest written");
+    Ok(())
+}
+
+// Returns the manifest of the search indexes for the versions of the docs in content/docs
+//
+// Each versioned folder has a search index that was copied from 'next' when the version's
+// snapshot was taken, apart from versions that were released before search was added.
+fn manifest_json() -> Result<String> {
+    let mut versions = Vec::new();
+    for entry in fs::read_dir(DOCS_VERSIONS_DIR)
+        .with_context(|| format!("failed to read '{DOCS_VERSIONS_DIR}'"))?
+    {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(version) = entry.file_name().to_str() {
+            if version == "next" || version_number(version).as_slice() >= FIRST_INDEXED_VERSION {
+                versions.push(version.to_string());
+            }
+        }
+    }
+
+    // 'next' comes first, followed by the released versions from newest to oldest
+    versions.sort_by_cached_key(|version| {
+        Reverse(if version == "next" {
+            vec![u64::MAX]
+        } else {
+            version_number(version)
+        })
+    });
+
+    let manifest = SearchManifest {
+        versions: versions
+            .into_iter()
+            .map(|version| ManifestEntry {
+                url: format!("/search-index-{version}.json"),
+                version,
+            })
+            .collect(),
+    };
+
+    serde_json::to_string(&manifest).context("failed to serialize the manifest as JSON")
+}
+
+// Returns the parts of a version number, e.g. `0.16` -> `[0, 16]`
+fn version_number(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|n| n.parse::<u64>().unwrap_or_default())
+        .collect()
+}
+
+#[derive(Serialize)]
+struct SearchManifest {
+    versions: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize)]
+struct ManifestEntry {
+    version: String,
+    url: String,
+}
+
 struct IndexBuilder {
     entries: Vec<SearchEntry>,
 }
 
 impl IndexBuilder {
     fn build_index() -> Result<Self> {
+        let manifest = DocsManifest::load()?;
         let mut builder = Self {
             entries: Vec::default(),
         };
 
-        builder.add_file(
-            "../modules/koto/docs/language_guide.md",
-            "/docs/next/language/",
-            "Language Guide",
-        )?;
-        builder.add_file("../modules/koto/docs/api.md", "/docs/next/api/", "API")?;
-        builder.add_file("../modules/koto/docs/cli.md", "/docs/next/cli/", "CLI")?;
-        builder.add_lib_dir(
-            "../modules/koto/docs/core_lib/",
-            "/docs/next/core/",
-            "Core Library",
-        )?;
-        builder.add_lib_dir(
-            "../modules/koto/docs/libs/",
-            "/docs/next/libs/",
-            "Extra Libs",
-        )?;
+        for page in manifest.pages.iter() {
+            if let Some(module) = &page.search_module {
+                let url = manifest
+                    .doc_url(Path::new(&page.source))
+                    .context("missing page URL")?;
+                builder.add_file(&DocsManifest::source_path(&page.source), &url, module)?;
+            }
+        }
+
+        for folder in manifest.folders.iter() {
+            if let Some(module) = &folder.search_module {
+                let url = manifest
+                    .doc_url(Path::new(&folder.source))
+                    .context("missing folder URL")?;
+                for path in manifest.folder_docs(folder)? {
+                    builder.add_lib_file(&path, &url, module)?;
+                }
+            }
+        }
 
         Ok(builder)
     }
@@ -63,17 +179,25 @@ impl IndexBuilder {
         }
     }
 
-    fn add_file(&mut self, path: &str, base_url: &str, module: &str) -> Result<()> {
+    fn add_file(&mut self, path: &Path, base_url: &str, module: &str) -> Result<()> {
         let contents =
-            fs::read_to_string(path).with_context(|| format!("failed to read '{path}'"))?;
+            fs::read_to_string(path).with_context(|| format!("failed to read '{path:?}'"))?;
 
         let mut parser = Parser::new(&contents).peekable();
+        // The doc's title would be the same for all sections, so it's left out of the breadcrumbs
+        let mut outline = DocOutline::new(false);
 
         // Add all non-empty sections as separate search entries
-        while let Some(entry) =
-            SearchEntry::parse_section(&mut parser, base_url, None, module, None, false)?
-        {
-            self.add_entry(entry);
+        while let Some(section) = Section::parse(&mut parser, None)? {
+            let (breadcrumbs, anchor) = outline.add_section(section.level, &section.name);
+            self.add_entry(SearchEntry {
+                title: section.name,
+                module: module.into(),
+                url: format!("{base_url}#{anchor}"),
+                breadcrumbs,
+                contents: section.contents,
+                keywords: section.keywords,
+            });
 
             if parser.peek().is_none() {
                 break;
@@ -83,24 +207,12 @@ impl IndexBuilder {
         Ok(())
     }
 
-    fn add_lib_dir(&mut self, dir: &str, base_url: &str, module: &str) -> Result<()> {
-        for entry in fs::read_dir(dir).with_context(|| format!("failed to read '{dir}'"))? {
-            let entry = entry?;
-
-            let path = entry.path();
-            if matches!(path.extension().and_then(OsStr::to_str), Some("md")) {
-                self.add_lib_file(&path, base_url, module)?;
-            }
-        }
-
-        Ok(())
-    }
-
     fn add_lib_file(&mut self, path: &Path, dir_url: &str, section_name: &str) -> Result<()> {
         let contents =
             fs::read_to_string(path).with_context(|| format!("failed to read '{path:?}'"))?;
 
         let mut parser = Parser::new(&contents).peekable();
+        let mut outline = DocOutline::new(true);
 
         let Some(module_name) = path
             .file_stem()
@@ -112,29 +224,46 @@ impl IndexBuilder {
 
         let base_url = format!("{dir_url}{module_name}/");
 
-        let Some(intro) = SearchEntry::parse_section(
-            &mut parser,
-            &base_url,
-            None,
-            section_name,
-            Some(HeadingLevel::H1),
-            false,
-        )?
-        else {
+        let Some(intro) = Section::parse(&mut parser, Some(HeadingLevel::H1))? else {
             bail!("Missing intro section in '{path:?}'");
         };
-        self.add_entry(intro);
-
-        // Add all H2 sections as separate search entries, skipping H3+ subsections
-        while let Some(entry) = SearchEntry::parse_section(
-            &mut parser,
-            &base_url,
-            Some(&module_name),
-            section_name,
-            None,
-            true,
-        )? {
-            self.add_entry(entry);
+        let (breadcrumbs, anchor) = outline.add_section(intro.level, &intro.name);
+        self.add_entry(SearchEntry {
+            title: intro.name,
+            module: section_name.into(),
+            url: format!("{base_url}#{anchor}"),
+            breadcrumbs,
+            contents: intro.contents,
+            keywords: intro.keywords,
+        });
+
+        // Add all H2 sections and their subsections as separate search entries
+        //
+        // H2 sections are titled with the module name as a prefix, e.g. `list.sort`, and their
+        // subsections are titled with the H2 section's title, e.g. `list.sort › Example`, given
+        // that subsections like `Example` are repeated throughout the docs.
+        let mut parent_title = None;
+        while let Some(section) = Section::parse(&mut parser, None)? {
+            let (breadcrumbs, anchor) = outline.add_section(section.level, &section.name);
+            let title = if section.level == HeadingLevel::H2 {
+                let title = format!("{module_name}.{}", section.name);
+                parent_title = Some(title.clone());
+                title
+            } else {
+                match &parent_title {
+                    Some(parent_title) => format!("{parent_title} › {}", section.name),
+                    None => section.name,
+                }
+            };
+
+            self.add_entry(SearchEntry {
+                title,
+                module: section_name.into(),
+                url: format!("{base_url}#{anchor}"),
+                breadcrumbs,
+                contents: section.contents,
+                keywords: section.keywords,
+            });
 
             if parser.peek().is_none() {
                 break;
@@ -145,30 +274,303 @@ impl IndexBuilder {
     }
 }
 
+// Keeps track of a doc's headings while its sections are being parsed
+struct DocOutline {
+    // The headings of the current section and the sections that contain it
+    parents: Vec<(HeadingLevel, String)>,
+    // The number of times that each anchor has been used in the doc
+    anchors: HashMap<String, usize>,
+    // Whether or not the doc's title should be included in the breadcrumbs
+    include_title: bool,
+}
+
+impl DocOutline {
+    fn new(include_title: bool) -> Self {
+        Self {
+            parents: Vec::new(),
+            anchors: HashMap::new(),
+            include_title,
+        }
+    }
+
+    // Returns the section's breadcrumbs, along with its anchor in the doc
+    //
+    // The breadcrumbs end with the section's own heading, e.g. `["list", "sort"]`.
+    fn add_section(&mut self, level: HeadingLevel, name: &str) -> (Vec<String>, String) {
+        while matches!(self.parents.last(), Some((parent_level, _)) if *parent_level >= level) {
+            self.parents.pop();
+        }
+
+        if level != HeadingLevel::H1 || self.include_title {
+            self.parents.push((level, name.to_string()));
+        }
+
+        let breadcrumbs = self
+            .parents
+            .iter()
+            .map(|(_, heading)| heading.clone())
+            .collect();
+
+        // Zola adds a numbered suffix to anchors that are already used in the doc
+        let slug = slugify(name);
+        let count = self.anchors.entry(slug.clone()).or_default();
+        let anchor = if *count == 0 {
+            slug
+        } else {
+            format!("{slug}-{count}")
+        };
+        *count += 1;
+
+        (breadcrumbs, anchor)
+    }
+}
+
 #[derive(Default, Serialize)]
 struct SearchEntry {
     title: String,
     module: String,
     url: String,
+    // The headings that lead to the entry's section, including its own heading
+    breadcrumbs: Vec<String>,
+    contents: String,
+    // Sorted so that the keywords' scores are added up in the same order in each build
+    keywords: BTreeSet<String>,
+}
+
+// The index that's loaded by the docs' search box
+//
+// Each entry's fields are tokenized and scored in advance, so that searching in the browser only
+// needs to look up the query's terms.
+#[derive(Serialize)]
+struct SearchIndex {
+    // The version of the docs that the index's entries belong to,
+    // updated by `version-snapshot` when the index is copied
+    version: String,
+    docs: Vec<IndexedDoc>,
+    // Each term's postings, flattened into alternating doc indices and scores
+    terms: BTreeMap<String, Vec<usize>>,
+}
+
+impl SearchIndex {
+    fn new(entries: &[SearchEntry]) -> Self {
+        let mut terms: BTreeMap<String, Vec<(usize, f64)>> = BTreeMap::new();
+
+        for (doc, entry) in entries.iter().enumerate() {
+            let mut doc_terms: HashMap<String, f64> = HashMap::new();
+
+            let mut add_field = |text: &str, weight: f64| {
+                let mut term_counts: HashMap<String, usize> = HashMap::new();
+                for term in tokenize(text) {
+                    *term_counts.entry(term).or_default() += 1;
+                }
+                for (term, count) in term_counts {
+                    // Repeated terms increase the score, but with diminishing returns
+                    *doc_terms.entry(term).or_default() += weight * (1.0 + (count as f64).ln());
+                }
+            };
+
+            add_field(&entry.title, TITLE_WEIGHT);
+            for keyword in &entry.keywords {
+                add_field(keyword, KEYWORDS_WEIGHT);
+            }
+            add_field(&entry.contents, CONTENTS_WEIGHT);
+            add_field(&entry.module, MODULE_WEIGHT);
+            for breadcrumb in &entry.breadcrumbs {
+                add_field(breadcrumb, MODULE_WEIGHT);
+            }
+
+            for (term, score) in doc_terms {
+                terms.entry(term).or_default().push((doc, score));
+            }
+        }
+
+        // Terms that appear in fewer docs are given higher scores
+        let doc_count = entries.len() as f64;
+        let terms = terms
+            .into_iter()
+            .map(|(term, mut postings)| {
+                let idf = (1.0 + doc_count / postings.len() as f64).ln();
+                postings.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+                let postings = postings
+                    .into_iter()
+                    .flat_map(|(doc, score)| [doc, (score * idf).round().max(1.0) as usize])
+                    .collect();
+                (term, postings)
+            })
+            .collect();
+
+        let docs = entries
+            .iter()
+            .map(|entry| {
+                let (text, sentences) = snippet_text(&entry.contents);
+                IndexedDoc {
+                    title: entry.title.clone(),
+                    module: entry.module.clone(),
+                    url: entry.url.clone(),
+                    breadcrumbs: entry.breadcrumbs.clone(),
+                    text,
+                    sentences,
+                }
+            })
+            .collect();
+
+        Self {
+            version: "next".into(),
+            docs,
+            terms,
+        }
+    }
+}
+
+// The weights that are applied to the scores of the terms in each of an entry's fields
+const TITLE_WEIGHT: f64 = 8.0;
+const KEYWORDS_WEIGHT: f64 = 7.0;
+const CONTENTS_WEIGHT: f64 = 3.0;
+const MODULE_WEIGHT: f64 = 2.0;
+
+// The maximum length of a doc's snippet text, in bytes
+const SNIPPET_TEXT_LENGTH: usize = 400;
+
+// Common words that aren't useful as search terms, also used by js/search.js
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "be", "by", "for", "in", "is", "it", "of", "on", "or", "that",
+    "the", "this", "to", "with",
+];
+
+// The information about a doc that's needed to display it in the search results
+#[derive(Serialize)]
+struct IndexedDoc {
+    title: String,
+    module: String,
+    url: String,
+    breadcrumbs: Vec<String>,
+    // The text that's used to show a snippet of the doc in the search results
+    text: String,
+    // The offsets of the sentences in the text, in UTF-16 code units for use with JS strings
+    sentences: Vec<usize>,
+}
+
+// Splits text into lowercase stemmed terms
+//
+// The search script tokenizes queries in the same way, so any changes here need to be mirrored
+// in js/search.js.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|word| word.to_lowercase())
+        .filter(|word| !word.is_empty() && !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| stem(&word))
+}
+
+// A minimal suffix-stripping stemmer, so that e.g. 'sorts', 'sorted', and 'sorting' all match 'sort'
+fn stem(word: &str) -> String {
+    let mut result = if let Some(stem) = word.strip_suffix("sses") {
+        format!("{stem}ss")
+    } else if let Some(stem) = word.strip_suffix("ies").filter(|stem| stem.len() > 1) {
+        format!("{stem}y")
+    } else if word.len() > 3
+        && word.ends_with('s')
+        && !["ss", "us", "is"]
+            .iter()
+            .any(|suffix| word.ends_with(suffix))
+    {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    };
+
+    for suffix in ["ing", "ed"] {
+        if let Some(stem) = result.strip_suffix(suffix) {
+            if stem.len() >= 4 {
+                result.truncate(stem.len());
+                break;
+            }
+        }
+    }
+
+    result
+}
+
+// Returns the sentences from the start of the contents that fit in the snippet text, along with
+// the offset of each sentence in the text
+//
+// The search script looks for the sentence that best matches the query, falling back to the
+// first sentence.
+fn snippet_text(contents: &str) -> (String, Vec<usize>) {
+    let contents = contents.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let mut text = String::new();
+    let mut sentences = Vec::new();
+
+    for sentence in split_sentences(&contents) {
+        if !text.is_empty() {
+            if text.len() + sentence.len() >= SNIPPET_TEXT_LENGTH {
+                break;
+            }
+            text.push(' ');
+        }
+        sentences.push(text.encode_utf16().count());
+        text.push_str(sentence);
+    }
+
+    // The first sentence is always included, so it's truncated if it's too long
+    if text.len() > SNIPPET_TEXT_LENGTH {
+        let mut end = SNIPPET_TEXT_LENGTH;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let end = text[..end].rfind(' ').unwrap_or(end);
+        text.truncate(end);
+        text.push('…');
+    }
+
+    (text, sentences)
+}
+
+// Splits text into sentences
+//
+// A sentence ends with punctuation that's followed by a space and then an uppercase letter,
+// which avoids splitting at the dots in code like `list.sort`.
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        if matches!(c, '.' | '!' | '?')
+            && text[i + 1..]
+                .strip_prefix(' ')
+                .is_some_and(|rest| rest.starts_with(char::is_uppercase))
+        {
+            result.push(&text[start..=i]);
+            start = i + 2;
+        }
+    }
+
+    if start < text.len() {
+        result.push(&text[start..]);
+    }
+
+    result
+}
+
+// A section of a doc, from its heading up until the next heading
+struct Section {
+    name: String,
+    level: HeadingLevel,
     contents: String,
-    keywords: HashSet<String>,
+    keywords: BTreeSet<String>,
 }
 
-impl SearchEntry {
-    fn parse_section(
+impl Section {
+    fn parse(
         parser: &mut Peekable<Parser>,
-        base_url: &str,
-        title_prefix: Option<&str>,
-        module: &str,
         mut level_to_consume: Option<HeadingLevel>,
-        skip_sub_sections: bool,
-    ) -> Result<Option<SearchEntry>> {
+    ) -> Result<Option<Section>> {
         use pulldown_cmark::{CodeBlockKind, Event::*, Tag, TagEnd};
         use std::cmp::Ordering::*;
 
         let mut contents = String::new();
         let mut section_name = String::new();
-        let mut keywords = HashSet::new();
+        let mut keywords = BTreeSet::new();
         let mut keyword = String::new();
 
         #[derive(Debug)]
@@ -176,7 +578,6 @@ impl SearchEntry {
             WaitingForSectionStart,
             Any,
             Section,
-            SubSection,
             Code,
             TypeDeclaration,
         }
@@ -207,18 +608,8 @@ impl SearchEntry {
                 }
             } else {
                 match peeked {
-                    Start(Tag::Heading { level, .. }) => {
-                        if let Some(level_to_consume) = level_to_consume {
-                            match level.cmp(&level_to_consume) {
-                                Greater if skip_sub_sections => {
-                                    parsing_mode = ParsingMode::SubSection
-                                }
-                                _ => break,
-                            }
-                        } else {
-                            unreachable!();
-                        }
-                    }
+                    // The section ends at the next heading
+                    Start(Tag::Heading { .. }) => break,
                     End(TagEnd::Heading(_)) => parsing_mode = ParsingMode::Any,
                     Start(Tag::Link { title, .. }) => contents.push_str(title),
                     End(TagEnd::Link) => {}
@@ -252,23 +643,19 @@ impl SearchEntry {
                             }
                         }
                         ParsingMode::Section => section_name.push_str(text),
-                        ParsingMode::SubSection => {
-                            debug_assert!(skip_sub_sections);
-                        }
                         ParsingMode::Code => {
                             // Skipping code
                         }
                         ParsingMode::TypeDeclaration => {
-                            // Skipping type declarations
+                            // Argument names and types in signatures are used as keywords,
+                            // e.g. `|List, key: Function| -> List`
+                            keywords.extend(type_declaration_identifiers(text));
                         }
                     },
                     Code(code) => match parsing_mode {
                         ParsingMode::Section => {
                             section_name.push_str(code);
                         }
-                        ParsingMode::SubSection => {
-                            debug_assert!(skip_sub_sections);
-                        }
                         ParsingMode::Any => {
                             contents.push_str(code);
                         }
@@ -283,19 +670,23 @@ impl SearchEntry {
             parser.next();
         }
 
-        let url = format!("{base_url}#{}", sluggify(&section_name, None));
-        let title = if let Some(prefix) = title_prefix {
-            format!("{prefix}.{section_name}")
-        } else {
-            section_name
+        let level = match (parsing_mode, level_to_consume) {
+            (ParsingMode::WaitingForSectionStart, _) | (_, None) => return Ok(None),
+            (_, Some(level)) => level,
         };
 
-        Ok(Some(SearchEntry {
-            title,
-            module: module.into(),
-            url,
+        Ok(Some(Section {
+            name: section_name,
+            level,
             contents,
             keywords,
         }))
     }
 }
+
+fn type_declaration_identifiers(declaration: &str) -> impl Iterator<Item = String> + '_ {
+    declaration
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| word.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+        .map(|word| word.to_string())
+}
@@ -0,0 +1,183 @@
+// The examples are run with the playground's runtime setup, which is shared with the
+// playground's tests, so that they behave in the same way as they do in the playground
+#[path = "../../playground/tests/common/mod.rs"]
+mod common;
+
+use anyhow::{bail, Context, Result};
+use koto::PtrMut;
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+use std::{fs, path::Path};
+
+use crate::docs_manifest::DocsManifest;
+
+pub fn run() -> Result<()> {
+    // The docs with examples to check are marked in the manifest
+    let manifest = DocsManifest::load()?;
+    let mut docs = Vec::new();
+    for page in manifest.pages.iter().filter(|page| page.check_examples) {
+        docs.push(DocsManifest::source_path(&page.source));
+    }
+    for folder in manifest
+        .folders
+        .iter()
+        .filter(|folder| folder.check_examples)
+    {
+        docs.extend(
+            manifest
+                .folder_docs(folder)?
+                .into_iter()
+                .filter(|doc| !folder.skips_examples(doc)),
+        );
+    }
+
+    let mut checked = 0;
+    let mut failed = 0;
+
+    for doc in docs {
+        for example in extract_examples(&doc)? {
+            if example.skip_run {
+                continue;
+            }
+
+            checked += 1;
+            if let Err(failure) = example.check() {
+                failed += 1;
+                eprintln!(
+                    "{}:{}\n{}\n",
+                    doc.to_string_lossy(),
+                    failure.line,
+                    failure.message
+                );
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{failed} of {checked} examples failed");
+    }
+
+    println!("{checked} examples checked");
+
+    Ok(())
+}
+
+struct Example {
+    // The line in the doc where the example's code block starts
+    line: usize,
+    script: String,
+    // The output that's expected from each of the example's `check!` lines, along with the
+    // line in the doc where it's found
+    expected_output: Vec<(usize, String)>,
+    skip_check: bool,
+    skip_run: bool,
+}
+
+// An example that failed, along with the line in the doc that the failure is reported at
+struct Failure {
+    line: usize,
+    message: String,
+}
+
+impl Example {
+    fn new(line: usize, code: &str) -> Self {
+        let mut result = Self {
+            line,
+            script: String::new(),
+            expected_output: Vec::new(),
+            skip_check: false,
+            skip_run: false,
+        };
+
+        for (index, code_line) in code.lines().enumerate() {
+            if code_line == "skip_check!" {
+                result.skip_check = true;
+            } else if code_line == "skip_run!" {
+                result.skip_run = true;
+            } else if let Some(expected) = code_line.strip_prefix("check!") {
+                // The code starts on the line following the code block's opening fence
+                let expected_line = line + index + 1;
+                result
+                    .expected_output
+                    .push((expected_line, expected.trim_start().to_string()));
+            } else {
+                result
+                    .script
+                    .push_str(&code_line.replace("print! ", "print "));
+                result.script.push('\n');
+            }
+        }
+
+        result
+    }
+
+    fn check(&self) -> Result<(), Failure> {
+        let output = PtrMut::from(String::new());
+        let mut koto = common::make_koto(&output);
+
+        if let Err(error) = common::run_script(&mut koto, &self.script) {
+            return Err(Failure {
+                line: self.line,
+                message: format!("Error while running example: {error}\n{}", self.script),
+            });
+        }
+
+        let output = output.borrow();
+        let expected_output: String = self
+            .expected_output
+            .iter()
+            .map(|(_, expected)| format!("{expected}\n"))
+            .collect();
+
+        if !self.skip_check && *output != expected_output {
+            // The mismatch is reported at the first `check!` line that doesn't match the output,
+            // or at the start of the example if there's unexpected extra output
+            let mut output_lines = output.lines();
+            let line = self
+                .expected_output
+                .iter()
+                .find(|(_, expected)| output_lines.next() != Some(expected.as_str()))
+                .map_or(self.line, |(line, _)| *line);
+
+            return Err(Failure {
+                line,
+                message: format!(
+                    "Output mismatch\n--- expected\n{expected_output}--- actual\n{output}"
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn extract_examples(path: &Path) -> Result<Vec<Example>> {
+    let markdown =
+        fs::read_to_string(path).with_context(|| format!("Failed to read '{path:?}'"))?;
+
+    let mut result = Vec::new();
+    let mut code_block = None;
+
+    for (event, range) in Parser::new(&markdown).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang)))
+                if lang.as_ref() == "koto" =>
+            {
+                let line = markdown[..range.start].matches('\n').count() + 1;
+                code_block = Some((line, String::new()));
+            }
+            Event::Text(text) => {
+                if let Some((_, code)) = code_block.as_mut() {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((line, code)) = code_block.take() {
+                    result.push(Example::new(line, &code));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(result)
+}
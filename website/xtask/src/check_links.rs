@@ -0,0 +1,406 @@
+//! Checks that the links in the docs point to existing pages and anchors
+//!
+//! The converted markdown in content/docs is always checked, and if the site has been built then
+//! the HTML in public/docs is checked too.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use slug::slugify;
+use walkdir::WalkDir;
+
+// The site's base URL, links that start with it are treated as internal links
+const SITE_URL: &str = "https://koto.dev";
+
+// Paths that are built separately from the docs, and so might be missing from static/
+const GENERATED_PATHS: &[&str] = &["/play"];
+
+pub fn run() -> Result<()> {
+    let mut checked = 0;
+    let mut broken = Vec::new();
+
+    let content = Site::from_content("content")?;
+    checked += content.check_links("content/docs", &mut broken);
+
+    if Path::new("public").exists() {
+        let public = Site::from_public("public")?;
+        checked += public.check_links("public/docs", &mut broken);
+    }
+
+    for link in broken.iter() {
+        eprintln!(
+            "{}: [{}]({}) - {}",
+            link.source.to_string_lossy(),
+            link.text,
+            link.target,
+            link.reason
+        );
+    }
+
+    if !broken.is_empty() {
+        bail!("{} of {checked} links are broken", broken.len());
+    }
+
+    println!("{checked} links checked");
+
+    Ok(())
+}
+
+// The pages of the site, along with the directory that contains its other files
+struct Site {
+    // The site's pages, keyed by URL, e.g. `/docs/next/core/list/`
+    pages: BTreeMap<String, Page>,
+    files_dir: PathBuf,
+}
+
+struct Page {
+    // The file that the page was loaded from
+    source: PathBuf,
+    anchors: HashSet<String>,
+    links: Vec<Link>,
+}
+
+struct Link {
+    text: String,
+    target: String,
+}
+
+struct BrokenLink {
+    source: PathBuf,
+    text: String,
+    target: String,
+    reason: String,
+}
+
+impl Site {
+    // Loads the pages from Zola's content folder
+    fn from_content(content_dir: &str) -> Result<Self> {
+        let mut pages = BTreeMap::new();
+
+        for entry in WalkDir::new(content_dir) {
+            let entry = entry.with_context(|| format!("error while traversing '{content_dir}'"))?;
+            let path = entry.path();
+            if path.extension() != Some(OsStr::new("md")) {
+                continue;
+            }
+
+            let markdown = fs::read_to_string(path)
+                .with_context(|| format!("failed to read '{}'", path.to_string_lossy()))?;
+            let (front_matter, markdown) = split_front_matter(&markdown);
+
+            let relative_dir = path
+                .parent()
+                .and_then(|parent| parent.strip_prefix(content_dir).ok())
+                .context("unexpected content path")?;
+            let mut url = format!("/{}", relative_dir.to_string_lossy());
+            if !url.ends_with('/') {
+                url.push('/');
+            }
+
+            // Section indexes are served at their folder's URL, other pages get their own folder
+            let file_stem = path.file_stem().and_then(OsStr::to_str).unwrap_or_default();
+            if file_stem != "_index" && file_stem != "index" {
+                let slug = front_matter_slug(front_matter)
+                    .with_context(|| format!("invalid front matter in '{path:?}'"))?
+                    .unwrap_or_else(|| file_stem.to_string());
+                url.push_str(&slug);
+                url.push('/');
+            }
+
+            pages.insert(url, Page::from_markdown(path, markdown));
+        }
+
+        Ok(Self {
+            pages,
+            files_dir: PathBuf::from("static"),
+        })
+    }
+
+    // Loads the pages from the site that Zola has built
+    fn from_public(public_dir: &str) -> Result<Self> {
+        let mut pages = BTreeMap::new();
+
+        for entry in WalkDir::new(public_dir) {
+            let entry = entry.with_context(|| format!("error while traversing '{public_dir}'"))?;
+            let path = entry.path();
+            if path.file_name() != Some(OsStr::new("index.html")) {
+                continue;
+            }
+
+            let html = fs::read_to_string(path)
+                .with_context(|| format!("failed to read '{}'", path.to_string_lossy()))?;
+
+            let relative_dir = path
+                .parent()
+                .and_then(|parent| parent.strip_prefix(public_dir).ok())
+                .context("unexpected public path")?;
+            let mut url = format!("/{}", relative_dir.to_string_lossy());
+            if !url.ends_with('/') {
+                url.push('/');
+            }
+
+            pages.insert(url, Page::from_html(path, &html));
+        }
+
+        Ok(Self {
+            pages,
+            files_dir: PathBuf::from(public_dir),
+        })
+    }
+
+    // Checks the links in the pages that were loaded from `dir`, returning the number of checked
+    // links
+    fn check_links(&self, dir: &str, broken: &mut Vec<BrokenLink>) -> usize {
+        let mut checked = 0;
+
+        for (url, page) in self.pages.iter() {
+            if !page.source.starts_with(dir) {
+                continue;
+            }
+
+            for link in page.links.iter() {
+                let Some(target) = internal_link_target(&link.target) else {
+                    continue;
+                };
+
+                checked += 1;
+                if let Err(reason) = self.resolve(url, target) {
+                    broken.push(BrokenLink {
+                        source: page.source.clone(),
+                        text: link.text.clone(),
+                        target: link.target.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+
+        checked
+    }
+
+    // Resolves a link from the page at `page_url`, returning a description of the problem if
+    // the link is broken
+    fn resolve(&self, page_url: &str, target: &str) -> Result<(), String> {
+        let (target, anchor) = match target.split_once('#') {
+            Some((target, anchor)) => (target, Some(anchor)),
+            None => (target, None),
+        };
+        let path = target.split('?').next().unwrap_or_default();
+
+        let url = resolve_path(page_url, path);
+        let page_url = if url.ends_with('/') {
+            url.clone()
+        } else {
+            format!("{url}/")
+        };
+
+        if let Some(page) = self.pages.get(&page_url) {
+            match anchor {
+                Some(anchor) if !anchor.is_empty() && !page.anchors.contains(anchor) => {
+                    Err(format!("missing anchor '#{anchor}' in '{page_url}'"))
+                }
+                _ => Ok(()),
+            }
+        } else if self.files_dir.join(url.trim_start_matches('/')).exists()
+            || GENERATED_PATHS
+                .iter()
+                .any(|generated| url == *generated || url.starts_with(&format!("{generated}/")))
+        {
+            Ok(())
+        } else {
+            Err(format!("unknown page '{url}'"))
+        }
+    }
+}
+
+impl Page {
+    fn from_markdown(source: &Path, markdown: &str) -> Self {
+        let mut anchors = Anchors::default();
+        let mut links = Vec::new();
+        // The heading or link whose text is currently being captured
+        let mut heading: Option<(Option<String>, String)> = None;
+        let mut link: Option<Link> = None;
+
+        for event in Parser::new_ext(markdown, Options::ENABLE_HEADING_ATTRIBUTES) {
+            match event {
+                Event::Start(Tag::Heading { id, .. }) => {
+                    heading = Some((id.map(|id| id.to_string()), String::new()))
+                }
+                Event::End(TagEnd::Heading(_)) => {
+                    if let Some((id, text)) = heading.take() {
+                        // Headings with a custom id, e.g. `# Heading {#id}`, use it as their anchor
+                        match id {
+                            Some(id) => anchors.insert(id),
+                            None => anchors.insert_heading(&text),
+                        }
+                    }
+                }
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    link = Some(Link {
+                        text: String::new(),
+                        target: dest_url.to_string(),
+                    })
+                }
+                Event::End(TagEnd::Link) => links.extend(link.take()),
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some((_, heading_text)) = heading.as_mut() {
+                        heading_text.push_str(&text);
+                    }
+                    if let Some(link) = link.as_mut() {
+                        link.text.push_str(&text);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            source: source.to_path_buf(),
+            anchors: anchors.used,
+            links,
+        }
+    }
+
+    fn from_html(source: &Path, html: &str) -> Self {
+        let anchors = attribute_values(html, "id")
+            .into_iter()
+            .map(|id| id.to_string())
+            .collect();
+
+        let links = html
+            .split("<a ")
+            .skip(1)
+            .filter_map(|tag| {
+                let (attributes, rest) = tag.split_once('>')?;
+                let target = *attribute_values(attributes, "href").first()?;
+                let text = rest.split("</a>").next().unwrap_or_default();
+                Some(Link {
+                    text: strip_tags(text).trim().to_string(),
+                    target: target.replace("&amp;", "&"),
+                })
+            })
+            .collect();
+
+        Self {
+            source: source.to_path_buf(),
+            anchors,
+            links,
+        }
+    }
+}
+
+// The anchors that Zola generates for a page's headings
+#[derive(Default)]
+struct Anchors {
+    used: HashSet<String>,
+    counts: HashMap<String, usize>,
+}
+
+impl Anchors {
+    fn insert(&mut self, anchor: String) {
+        self.used.insert(anchor);
+    }
+
+    // Zola adds a numbered suffix to anchors that are already used in the page
+    fn insert_heading(&mut self, heading: &str) {
+        let slug = slugify(heading);
+        let count = self.counts.entry(slug.clone()).or_default();
+        let anchor = if *count == 0 {
+            slug
+        } else {
+            format!("{slug}-{count}")
+        };
+        *count += 1;
+        self.used.insert(anchor);
+    }
+}
+
+// Returns the link's target if it points to a page on the site
+fn internal_link_target(target: &str) -> Option<&str> {
+    if let Some(path) = target.strip_prefix(SITE_URL) {
+        Some(path)
+    } else if target.contains("://") || target.starts_with("mailto:") {
+        None
+    } else {
+        Some(target)
+    }
+}
+
+// Resolves a link's path relative to the URL of the page that contains it
+//
+// Page URLs end with a slash, so e.g. `../core` from `/docs/next/language/` resolves to
+// `/docs/next/core`.
+fn resolve_path(page_url: &str, path: &str) -> String {
+    if path.is_empty() {
+        return page_url.to_string();
+    }
+
+    let mut segments: Vec<&str> = if path.starts_with('/') {
+        Vec::new()
+    } else {
+        page_url.split('/').filter(|s| !s.is_empty()).collect()
+    };
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    let mut result = format!("/{}", segments.join("/"));
+    if path.ends_with('/') && result != "/" {
+        result.push('/');
+    }
+    result
+}
+
+// Returns the page's slug if it's set in its front matter
+fn front_matter_slug(front_matter: &str) -> Result<Option<String>> {
+    let front_matter: toml::Table = toml::from_str(front_matter)?;
+    Ok(front_matter
+        .get("slug")
+        .and_then(|slug| slug.as_str())
+        .map(|slug| slug.to_string()))
+}
+
+// Splits a page's TOML front matter from its markdown
+fn split_front_matter(page: &str) -> (&str, &str) {
+    page.strip_prefix("+++")
+        .and_then(|rest| rest.split_once("\n+++"))
+        .unwrap_or(("", page))
+}
+
+// Returns the values of an attribute in an HTML string, e.g. `id="foo"`
+fn attribute_values<'a>(html: &'a str, attribute: &str) -> Vec<&'a str> {
+    let pattern = format!(" {attribute}=\"");
+    html.match_indices(&pattern)
+        .filter_map(|(start, _)| {
+            let value = &html[start + pattern.len()..];
+            value.split_once('"').map(|(value, _)| value)
+        })
+        .collect()
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
@@ -0,0 +1,61 @@
+//! Compares generated files with the files on disk, used by tasks that are run with `--check`
+
+use std::{fs, path::Path};
+
+use anyhow::{bail, Result};
+use similar::{ChangeTag, TextDiff};
+
+// Compares each generated file with the file at its path, and checks that the stale files have
+// been removed
+//
+// A summary of the differences is printed for each out-of-date file. Nothing is written to disk.
+pub fn check<'a>(
+    outputs: impl IntoIterator<Item = (&'a Path, &'a str)>,
+    stale: impl IntoIterator<Item = &'a Path>,
+    task: &str,
+) -> Result<()> {
+    let mut checked = 0;
+    let mut out_of_date = 0;
+
+    for (path, generated) in outputs {
+        checked += 1;
+
+        let summary = match fs::read_to_string(path) {
+            Ok(existing) if existing == generated => continue,
+            Ok(existing) => {
+                let diff = TextDiff::from_lines(existing.as_str(), generated);
+                let (mut added, mut removed) = (0, 0);
+                for change in diff.iter_all_changes() {
+                    match change.tag() {
+                        ChangeTag::Insert => added += 1,
+                        ChangeTag::Delete => removed += 1,
+                        ChangeTag::Equal => {}
+                    }
+                }
+                format!("changed (+{added} -{removed} lines)")
+            }
+            Err(_) => "missing".into(),
+        };
+
+        out_of_date += 1;
+        eprintln!("{}: {summary}", path.to_string_lossy());
+    }
+
+    for path in stale {
+        if path.exists() {
+            out_of_date += 1;
+            eprintln!(
+                "{}: stale, its source has been removed",
+                path.to_string_lossy()
+            );
+        }
+    }
+
+    if out_of_date > 0 {
+        bail!("{out_of_date} file(s) are out of date, run `cargo xtask {task}` to update them");
+    }
+
+    println!("{checked} file(s) are up to date");
+
+    Ok(())
+}
@@ -2,152 +2,189 @@ use anyhow::{bail, Context, Result};
 use pulldown_cmark::{CodeBlockKind, CowStr, Event, HeadingLevel, Parser, Tag, TagEnd};
 use pulldown_cmark_to_cmark::cmark;
 use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
     fs,
     iter::once,
     ops::Deref,
     path::{Path, PathBuf},
 };
 
-pub fn run() -> Result<()> {
-    convert_single_page_doc(
-        "about.md",
-        "content/about",
-        r#"+++
-title = "About"
-template = "single-page.html"
-insert_anchor_links = "heading"
-+++
-"#,
-        true,
-        false,
-        FixUrlMode::TopLevelToNext,
-    )?;
-    convert_single_page_doc(
-        "language_guide.md",
-        "content/docs/next/language",
-        r#"+++
-title = "Language Guide"
-template = "docs-guide.html"
-insert_anchor_links = "heading"
-weight = 1
-+++
+use crate::{
+    check_outputs, doc_links,
+    docs_manifest::{DocsManifest, FolderDocs, PageDoc, DOCS_DIR},
+};
 
-# The Koto Language Guide
+// A list of the files that were generated by the last conversion, used to find stale outputs
+const GENERATED_FILES_LIST: &str = ".generated-docs";
 
-As you're reading this guide, you're encouraged to play around with the examples to get a feel
-for the language.
+// Converts the docs, or with `check` set, checks that the converted docs are up to date
+// without writing anything
+pub fn run(check: bool) -> Result<()> {
+    let manifest = DocsManifest::load()?;
+    let outputs = convert_docs(&manifest)?;
 
-When you see a <span uk-icon="play"></span> icon below an example,
-clicking it will open the example in the [Koto Playground](/play),
-where you can run the code and see what happens as you make changes.
+    // Files that were generated previously but no longer have a source doc
+    let previous = match fs::read_to_string(GENERATED_FILES_LIST) {
+        Ok(list) => list.lines().map(PathBuf::from).collect(),
+        Err(_) => BTreeSet::new(),
+    };
+    let mut stale: BTreeSet<PathBuf> = previous
+        .into_iter()
+        .filter(|path| !outputs.contains_key(path))
+        .collect();
+    // The list of generated files isn't checked in, so the output folders are also checked for
+    // docs without a source
+    for folder in manifest.folders.iter() {
+        stale.extend(unknown_folder_outputs(folder, &outputs)?);
+    }
 
-You can also try out the examples using the [Koto CLI](../cli).
-"#,
-        true,
-        true,
-        FixUrlMode::TopLevel,
-    )?;
-    convert_doc_folder(
-        "../modules/koto/docs/core_lib",
-        "content/docs/next/core",
-        true,
-    )?;
-    convert_doc_folder("../modules/koto/docs/libs", "content/docs/next/libs", false)?;
-    convert_single_page_doc(
-        "api.md",
-        "content/docs/next/api",
-        r#"+++
-title = "Rust API"
-template = "docs-guide.html"
-insert_anchor_links = "heading"
-weight = 4
-+++
-"#,
-        true,
-        false,
-        FixUrlMode::TopLevel,
-    )?;
-    convert_single_page_doc(
-        "cli.md",
-        "content/docs/next/cli",
-        r#"+++
-title = "Koto CLI"
-template = "docs-guide.html"
-insert_anchor_links = "heading"
-weight = 5
-+++
-"#,
-        false,
-        false,
-        FixUrlMode::TopLevel,
-    )?;
+    if check {
+        return check_outputs::check(
+            outputs
+                .iter()
+                .map(|(path, contents)| (path.as_path(), contents.as_str())),
+            stale.iter().map(|path| path.as_path()),
+            "docs",
+        );
+    }
+
+    for (path, contents) in outputs.iter() {
+        fs::write(path, contents)
+            .with_context(|| format!("Failed to write output file '{path:?}'"))?;
+    }
 
-    println!("Docs updated");
+    let removed = remove_stale_outputs(&stale)?;
+
+    let generated_list: String = outputs
+        .keys()
+        .map(|path| format!("{}\n", path.to_string_lossy()))
+        .collect();
+    fs::write(GENERATED_FILES_LIST, generated_list)
+        .with_context(|| format!("Failed to write '{GENERATED_FILES_LIST}'"))?;
+
+    if removed.is_empty() {
+        println!("Docs updated");
+    } else {
+        println!("Docs updated, {} stale doc(s) removed", removed.len());
+    }
 
     Ok(())
 }
 
-fn convert_single_page_doc(
-    input_file: &str,
-    output_dir: &str,
-    intro: &str,
-    skip_preamble: bool,
-    skip_title: bool,
-    fix_url_mode: FixUrlMode,
-) -> Result<()> {
-    use std::io::Write;
+// Converts all of the docs in the manifest, returning the contents of each output file
+fn convert_docs(manifest: &DocsManifest) -> Result<BTreeMap<PathBuf, String>> {
+    let mut outputs = BTreeMap::new();
+
+    for page in manifest.pages.iter() {
+        let (path, contents) = convert_single_page_doc(manifest, page)?;
+        outputs.insert(path, contents);
+    }
+
+    for folder in manifest.folders.iter() {
+        outputs.extend(convert_doc_folder(manifest, folder)?);
+    }
+
+    Ok(outputs)
+}
+
+// Returns the docs in a folder's output directory that weren't generated from a source doc
+//
+// The output directories also contain a hand-written `_index.md` for their section.
+fn unknown_folder_outputs(
+    folder: &FolderDocs,
+    outputs: &BTreeMap<PathBuf, String>,
+) -> Result<Vec<PathBuf>> {
+    let output_dir = Path::new(&folder.output);
+    if !output_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut result = Vec::new();
+
+    for entry in
+        fs::read_dir(output_dir).with_context(|| format!("Failed to read '{output_dir:?}'"))?
+    {
+        let path = entry?.path();
+        if path.extension() == Some(OsStr::new("md"))
+            && path.file_name() != Some(OsStr::new("_index.md"))
+            && !outputs.contains_key(&path)
+        {
+            result.push(path);
+        }
+    }
+
+    Ok(result)
+}
+
+// Removes the previously generated files that no longer have a source doc
+fn remove_stale_outputs(stale: &BTreeSet<PathBuf>) -> Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+
+    for path in stale.iter() {
+        if !path.exists() {
+            continue;
+        }
+
+        fs::remove_file(path).with_context(|| format!("Failed to remove '{path:?}'"))?;
+        println!("Removed stale doc '{}'", path.to_string_lossy());
+        removed.push(path.clone());
+    }
+
+    Ok(removed)
+}
 
-    let mut input_path = PathBuf::from("../modules/koto/docs/");
-    input_path.push(input_file);
+fn convert_single_page_doc(manifest: &DocsManifest, page: &PageDoc) -> Result<(PathBuf, String)> {
+    let input_path = DocsManifest::source_path(&page.source);
 
-    let mut output_path = PathBuf::from(output_dir);
+    let mut output_path = PathBuf::from(&page.output);
     output_path.push("_index.md");
-    let mut output_file = fs::File::create(&output_path)
-        .with_context(|| format!("Failed to create output file '{output_path:?}'"))?;
-    write!(output_file, "{intro}")?;
 
     let converted = convert_doc(
+        manifest,
         &input_path,
         ConvertDocFlags {
             generate_front_matter: false,
-            skip_preamble,
-            skip_title,
-            add_playground_links: true,
-            fix_url_mode,
+            skip_preamble: page.skip_preamble,
+            skip_title: page.skip_title,
+            add_playground_links: !page.skip_playground_links,
         },
     )?;
-    write!(output_file, "\n\n{converted}")?;
 
-    Ok(())
-}
+    let output = format!(
+        "+++\n{}+++\n{}\n\n{converted}",
+        page.front_matter, page.intro
+    );
 
-fn convert_doc_folder(input: &str, output: &str, add_playground_links: bool) -> Result<()> {
-    use std::io::Write;
+    Ok((output_path, output))
+}
 
-    let output_dir = PathBuf::from(output);
+fn convert_doc_folder(
+    manifest: &DocsManifest,
+    folder: &FolderDocs,
+) -> Result<Vec<(PathBuf, String)>> {
+    let output_dir = PathBuf::from(&folder.output);
+    let mut result = Vec::new();
 
-    for doc in fs::read_dir(input)? {
-        let doc_path = doc?.path();
+    for doc_path in manifest.folder_docs(folder)? {
         let converted = convert_doc(
+            manifest,
             &doc_path,
             ConvertDocFlags {
                 generate_front_matter: true,
                 skip_preamble: false,
                 skip_title: false,
-                add_playground_links,
-                fix_url_mode: FixUrlMode::SubFolder,
+                add_playground_links: !folder.skip_playground_links
+                    && !folder.skips_examples(&doc_path),
             },
         )?;
 
         let mut output_path = output_dir.clone();
         output_path.push(doc_path.file_name().unwrap());
-        let mut output_file = fs::File::create(&output_path)
-            .with_context(|| format!("Failed to create output file '{output_path:?}'"))?;
-
-        write!(output_file, "{converted}")?;
+        result.push((output_path, converted));
     }
 
-    Ok(())
+    Ok(result)
 }
 
 fn skip_until<'a>(input: &'a str, token: &str) -> Result<&'a str> {
@@ -166,20 +203,13 @@ struct ConvertDocFlags {
     skip_preamble: bool,
     skip_title: bool,
     add_playground_links: bool,
-    fix_url_mode: FixUrlMode,
-}
-
-#[derive(Copy, Clone)]
-enum FixUrlMode {
-    // Adjust doc links to docs/next
-    TopLevelToNext,
-    // Adjust doc links to neighboring docs version
-    TopLevel,
-    // Adjust doc links to neighboring docs version from docs subfolder
-    SubFolder,
 }
 
-fn convert_doc(input_path: &Path, flags: ConvertDocFlags) -> Result<String> {
+fn convert_doc(
+    manifest: &DocsManifest,
+    input_path: &Path,
+    flags: ConvertDocFlags,
+) -> Result<String> {
     use std::fmt::Write;
     use Event::*;
 
@@ -244,7 +274,28 @@ slug = \"{slug}\"
     // Parse the input markdown and perform some modifications
     // Each event is converted into an iterator providing modified events,
     // with flat_map merging the iterators back into a single event stream.
-    let parser = Parser::new(input).flat_map({
+    // Links to other docs are resolved to their URLs on the site
+    let source = input_path
+        .strip_prefix(DOCS_DIR)
+        .with_context(|| format!("'{input_path:?}' is outside of the docs folder"))?;
+    let events = Parser::new(input)
+        .map(|event| match event {
+            Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Ok(Start(Tag::Link {
+                link_type,
+                dest_url: doc_links::resolve_link(manifest, source, &dest_url)?.into(),
+                title,
+                id,
+            })),
+            _ => Ok(event),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let parser = events.into_iter().flat_map({
         // Add a playground link to every koto code block
         let mut in_koto_code = false;
         let mut in_rust_include = false;
@@ -266,21 +317,6 @@ slug = \"{slug}\"
                     _ => once(event).chain(None),
                 }
             }
-            Start(Tag::Link {
-                link_type,
-                dest_url,
-                title,
-                id,
-            }) => {
-                let fixed_url = fix_doc_urls(&dest_url, flags.fix_url_mode).unwrap().into();
-                once(Start(Tag::Link {
-                    link_type,
-                    dest_url: fixed_url,
-                    title,
-                    id,
-                }))
-                .chain(None)
-            }
             End(TagEnd::CodeBlock) if in_koto_code => {
                 in_koto_code = false;
                 if flags.add_playground_links {
@@ -292,7 +328,7 @@ slug = \"{slug}\"
                         .replace("skip_run!\n", "");
                     let shortcode = format!(
                         "\
-{{% example_playground_link() %}}
+{{% example_playground_link(embed = true) %}}
 {playground_code}
 {{% end %}}
 "
@@ -330,36 +366,3 @@ slug = \"{slug}\"
 
     Ok(output_buffer)
 }
-
-fn fix_doc_urls(url: &str, mode: FixUrlMode) -> Result<String> {
-    use FixUrlMode::*;
-
-    let result = match mode {
-        TopLevelToNext => url
-            .replace("./language_guide.md", "/docs/next/language/")
-            .replace("./cli.md", "/docs/next/cli/")
-            .replace("./api.md", "/docs/next/api/")
-            .replace("./core_lib", "/docs/next/core/")
-            .replace("./libs", "/docs/next/libs/"),
-        TopLevel => url
-            .replace("./core_lib", "../core")
-            .replace("./libs", "../libs")
-            .replace("./cli.md", "../cli")
-            .replace("./api.md", "../api")
-            .replace("./language_guide.md", "../language/"),
-        SubFolder => url.replace("../language_guide.md", "../../language/"),
-    };
-
-    let result = if result.starts_with('#') || url.contains(".md#") {
-        // Replace underscores with hyphens in local anchor links
-        result.replace('_', "-")
-    } else {
-        result
-    };
-
-    let result = result
-        // Strip out .md suffixes
-        .replace(".md", "");
-
-    Ok(result)
-}
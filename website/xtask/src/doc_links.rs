@@ -0,0 +1,94 @@
+//! Resolves the links between Koto's docs to URLs on the website
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use slug::slugify;
+
+use crate::docs_manifest::DocsManifest;
+
+// Links between pages in this folder are made relative, so that they keep working in
+// versioned snapshots of the docs
+const VERSIONED_DOCS_URL: &str = "/docs/next/";
+
+// Resolves a link found in one of Koto's docs to a URL on the site
+//
+// `source` is the path of the doc that contains the link, relative to the docs folder, and the
+// docs' URLs are looked up in the manifest. Anchors are slugified in the same way as Zola's
+// heading anchors.
+pub fn resolve_link(manifest: &DocsManifest, source: &Path, link: &str) -> Result<String> {
+    // External and absolute links are left unchanged
+    if link.contains("://") || link.starts_with("mailto:") || link.starts_with('/') {
+        return Ok(link.to_string());
+    }
+
+    let (path, anchor) = match link.split_once('#') {
+        Some((path, anchor)) => (path, Some(format!("#{}", slugify(anchor)))),
+        None => (link, None),
+    };
+
+    if path.is_empty() {
+        return Ok(anchor.unwrap_or_default());
+    }
+
+    let source_url = manifest
+        .doc_url(source)
+        .with_context(|| format!("unknown doc '{source:?}'"))?;
+
+    let target = normalize(&source.parent().unwrap_or(Path::new("")).join(path));
+    let target_url = match manifest.doc_url(&target) {
+        Some(url) if DocsManifest::source_path(&target.to_string_lossy()).exists() => url,
+        _ => bail!(
+            "link to unknown doc '{link}' in '{}'",
+            source.to_string_lossy()
+        ),
+    };
+
+    let url = if source_url.starts_with(VERSIONED_DOCS_URL)
+        && target_url.starts_with(VERSIONED_DOCS_URL)
+    {
+        relative_url(&source_url, &target_url)
+    } else {
+        target_url
+    };
+
+    Ok(format!("{url}{}", anchor.unwrap_or_default()))
+}
+
+// Removes `.` and `..` components from a relative path
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            other => result.push(other),
+        }
+    }
+
+    result
+}
+
+// Returns a relative URL from one page to another, e.g.
+// `/docs/next/core/list/` -> `/docs/next/core/io/` gives `../io/`
+fn relative_url(from: &str, to: &str) -> String {
+    let from: Vec<_> = from.split('/').filter(|s| !s.is_empty()).collect();
+    let to: Vec<_> = to.split('/').filter(|s| !s.is_empty()).collect();
+
+    let shared = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut result = "../".repeat(from.len() - shared);
+    for segment in &to[shared..] {
+        result.push_str(segment);
+        result.push('/');
+    }
+
+    if result.is_empty() {
+        result.push_str("./");
+    }
+
+    result
+}
@@ -0,0 +1,188 @@
+//! The manifest of Koto's docs, see xtask/docs.toml
+
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use walkdir::WalkDir;
+
+// The folder in the Koto repo that contains the docs
+pub const DOCS_DIR: &str = "../modules/koto/docs/";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DocsManifest {
+    // Files or folders in the docs folder that aren't converted
+    #[serde(default)]
+    pub ignored: Vec<String>,
+    #[serde(default, rename = "page")]
+    pub pages: Vec<PageDoc>,
+    #[serde(default, rename = "folder")]
+    pub folders: Vec<FolderDocs>,
+}
+
+// A single doc that's converted into a section's _index.md
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PageDoc {
+    pub source: String,
+    pub output: String,
+    // Skips the doc's contents up until its first `---`
+    #[serde(default)]
+    pub skip_preamble: bool,
+    // Skips the doc's contents up until after its title
+    #[serde(default)]
+    pub skip_title: bool,
+    // The Zola front matter for the output page, without the `+++` delimiters
+    pub front_matter: String,
+    // Markdown that's inserted before the doc's contents
+    #[serde(default)]
+    pub intro: String,
+    // The module name that's shown in search results, the doc isn't indexed if it's not set
+    pub search_module: Option<String>,
+    // Whether or not the doc's examples are run by `cargo xtask check-examples`
+    #[serde(default)]
+    #[cfg_attr(not(feature = "check-examples"), allow(dead_code))]
+    pub check_examples: bool,
+    // Leaves out the links that open the doc's examples in the playground
+    #[serde(default)]
+    pub skip_playground_links: bool,
+}
+
+// A folder of docs that are each converted into a page in the output folder
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FolderDocs {
+    pub source: String,
+    pub output: String,
+    // The module name that's shown in search results, the docs aren't indexed if it's not set
+    pub search_module: Option<String>,
+    // Whether or not the docs' examples are run by `cargo xtask check-examples`
+    #[serde(default)]
+    #[cfg_attr(not(feature = "check-examples"), allow(dead_code))]
+    pub check_examples: bool,
+    // Leaves out the links that open the docs' examples in the playground
+    #[serde(default)]
+    pub skip_playground_links: bool,
+    // Docs in the folder with examples that can't run in the playground, e.g. for libs that
+    // aren't available there. The examples aren't checked and don't get playground links.
+    #[serde(default)]
+    pub skip_examples: Vec<String>,
+}
+
+impl FolderDocs {
+    // Returns true if the doc's examples are skipped, see `skip_examples`
+    pub fn skips_examples(&self, doc: &Path) -> bool {
+        doc.file_name()
+            .and_then(OsStr::to_str)
+            .is_some_and(|name| self.skip_examples.iter().any(|skipped| skipped == name))
+    }
+}
+
+impl DocsManifest {
+    // Loads the manifest, checking that it covers all of the files in the docs folder
+    pub fn load() -> Result<Self> {
+        let manifest: Self = toml::from_str(include_str!("../docs.toml"))
+            .context("failed to parse xtask/docs.toml")?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    // Returns the site URL for a doc, e.g. `core_lib/list.md` -> `/docs/next/core/list/`
+    //
+    // The doc's path is relative to the docs folder.
+    pub fn doc_url(&self, doc: &Path) -> Option<String> {
+        let doc = doc.to_str()?.trim_end_matches('/');
+
+        if let Some(page) = self.pages.iter().find(|page| page.source == doc) {
+            return Some(site_url(&page.output));
+        }
+
+        self.folders.iter().find_map(|folder| {
+            if doc == folder.source {
+                Some(site_url(&folder.output))
+            } else {
+                let name = doc
+                    .strip_prefix(&folder.source)?
+                    .strip_prefix('/')?
+                    .strip_suffix(".md")?;
+                (!name.contains('/')).then(|| format!("{}{name}/", site_url(&folder.output)))
+            }
+        })
+    }
+
+    // Returns the path to a doc in the Koto repo
+    pub fn source_path(source: &str) -> PathBuf {
+        Path::new(DOCS_DIR).join(source)
+    }
+
+    // Returns the paths to the markdown docs in a folder, leaving out any ignored docs
+    //
+    // The paths are sorted so that the docs are processed in the same order each time.
+    pub fn folder_docs(&self, folder: &FolderDocs) -> Result<Vec<PathBuf>> {
+        let dir = Self::source_path(&folder.source);
+        let mut result = Vec::new();
+
+        for entry in fs::read_dir(&dir).with_context(|| format!("failed to read '{dir:?}'"))? {
+            let path = entry?.path();
+            if path.extension() != Some(OsStr::new("md")) {
+                continue;
+            }
+
+            let doc = Path::new(&folder.source).join(path.file_name().unwrap_or_default());
+            if !self.is_ignored(&doc) {
+                result.push(path);
+            }
+        }
+
+        result.sort();
+        Ok(result)
+    }
+
+    // Returns true if the doc is ignored, the doc's path is relative to the docs folder
+    fn is_ignored(&self, doc: &Path) -> bool {
+        self.ignored
+            .iter()
+            .any(|ignored| doc.starts_with(ignored.trim_end_matches('/')))
+    }
+
+    // Checks that every file in the docs folder is either mapped to an output or ignored
+    fn validate(&self) -> Result<()> {
+        let mut unmapped = Vec::new();
+
+        for entry in WalkDir::new(DOCS_DIR) {
+            let entry = entry.with_context(|| format!("error while traversing '{DOCS_DIR}'"))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let path = entry
+                .path()
+                .strip_prefix(DOCS_DIR)
+                .context("unexpected docs path")?;
+
+            if !self.is_ignored(path) && self.doc_url(path).is_none() {
+                unmapped.push(path.to_string_lossy().to_string());
+            }
+        }
+
+        if !unmapped.is_empty() {
+            bail!(
+                "The following docs aren't mapped or ignored in xtask/docs.toml:\n  {}",
+                unmapped.join("\n  ")
+            );
+        }
+
+        Ok(())
+    }
+}
+
+// Returns the URL that Zola serves a content folder at, e.g. `content/about` -> `/about/`
+fn site_url(output: &str) -> String {
+    let url = output.trim_start_matches("content").trim_end_matches('/');
+    format!("{url}/")
+}
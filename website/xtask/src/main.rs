@@ -3,8 +3,14 @@ use std::env::args;
 use anyhow::{bail, Result};
 
 mod build_search_index;
+#[cfg(feature = "check-examples")]
+mod check_examples;
+mod check_links;
+mod check_outputs;
 mod convert_docs;
 mod data;
+mod doc_links;
+mod docs_manifest;
 mod postprocess_playground;
 mod version_snapshot;
 
@@ -17,12 +23,14 @@ fn main() {
 
 fn try_main() -> Result<()> {
     match args().nth(1).as_deref() {
-        Some("docs") => convert_docs::run(),
+        Some("check-examples") => check_examples(),
+        Some("check-links") => check_links::run(),
+        Some("docs") => convert_docs::run(check_flag()),
         Some("playground") => match args().nth(2).as_ref() {
             Some(staging_dir) => postprocess_playground::run(staging_dir),
             None => bail!("Missing argument: staging dir"),
         },
-        Some("search-index") => build_search_index::run(),
+        Some("search-index") => build_search_index::run(check_flag()),
         Some("version-snapshot") => match args().nth(2).as_ref() {
             Some(version) => version_snapshot::run(version),
             _ => bail!("Missing argument: version"),
@@ -35,10 +43,32 @@ fn try_main() -> Result<()> {
     }
 }
 
+#[cfg(feature = "check-examples")]
+fn check_examples() -> Result<()> {
+    check_examples::run()
+}
+
+// The examples are run with Koto, which is only built when the feature is enabled
+#[cfg(not(feature = "check-examples"))]
+fn check_examples() -> Result<()> {
+    bail!("check-examples needs the `check-examples` feature, run it with `cargo xtask-examples`")
+}
+
+// Returns true if the task should only check its outputs, e.g. `cargo xtask docs --check`
+fn check_flag() -> bool {
+    args().skip(2).any(|arg| arg == "--check")
+}
+
 const HELP: &str = "\
 Tasks:
-docs                Converts Koto's docs for Zola
+check-examples      Runs the examples in Koto's docs and checks their output,
+                    needs the `check-examples` feature, see `cargo xtask-examples`
+check-links         Checks that the links in the docs point to existing pages and anchors
+docs [--check]      Converts Koto's docs for Zola,
+                    or checks that the converted docs are up to date
 playground          Postprocesses the Koto playground for integration in the main website
-search-index        Builds a search index for the docs in content/docs/next
+search-index [--check]
+                    Builds a search index for the docs in content/docs/next,
+                    or checks that the index is up to date
 version-snapshot    Takes a versioned snapshot of the docs and playground
 ";
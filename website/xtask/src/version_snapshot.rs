@@ -12,7 +12,7 @@ use std::{
 use anyhow::{bail, Context, Result};
 use walkdir::WalkDir;
 
-use crate::data::Data;
+use crate::{build_search_index, data::Data};
 
 pub fn run(version: &str) -> Result<()> {
     let docs_target = format!("content/docs/{version}");
@@ -44,6 +44,12 @@ pub fn run(version: &str) -> Result<()> {
         "/docs/next",
         &format!("/docs/{version}"),
     )?;
+    search_and_replace_in_file(
+        &search_index_target,
+        r#""version":"next""#,
+        &format!(r#""version":"{version}""#),
+    )?;
+    build_search_index::write_manifest()?;
 
     // Copy the playground
     if playground_target_path.exists() {
@@ -55,8 +61,18 @@ pub fn run(version: &str) -> Result<()> {
     println!("Playground copied to '{playground_target}'",);
 
     // Post-process the copied docs
-    let playground_link_search = "example_playground_link()";
-    let playground_link_replacement = format!("example_playground_link(version = \"{version}\")");
+    // Docs that were converted before inline playgrounds were added use the shortcode without
+    // the `embed` argument
+    let playground_link_replacements = [
+        (
+            "example_playground_link(embed = true)",
+            format!("example_playground_link(version = \"{version}\", embed = true)"),
+        ),
+        (
+            "example_playground_link()",
+            format!("example_playground_link(version = \"{version}\")"),
+        ),
+    ];
     for f in WalkDir::new(&docs_target_path) {
         let f = f.with_context(|| format!("error while traversing {docs_target}",))?;
         let path = f.path();
@@ -76,7 +92,9 @@ pub fn run(version: &str) -> Result<()> {
         }
 
         if path.extension() == Some(OsStr::new("md")) {
-            search_and_replace_in_file(path, playground_link_search, &playground_link_replacement)?;
+            for (search, replacement) in playground_link_replacements.iter() {
+                search_and_replace_in_file(path, search, replacement)?;
+            }
             println!("Updated playground links in '{}'", path.to_string_lossy());
         }
     }